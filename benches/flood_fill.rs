@@ -0,0 +1,35 @@
+//! Times the cascade from a single reveal on a huge, sparse custom board.
+//!
+//! Run with `cargo bench --bench flood_fill`. The board is 1000x1000 with a handful of mines, so nearly
+//! every square is uncovered by one click; a recursive fill would overflow the stack here.
+
+use std::time::{Duration, Instant};
+
+use minesweeper_rs::{config::{BoardLimits, Config, Difficulty}, grid::{GameBoard, GameState}, util::ModernRandomNumberGenerator};
+
+const ITERATIONS: u32 = 10;
+
+fn main() {
+    let limits = BoardLimits { max_width: 1000, max_height: 1000, ..BoardLimits::CLASSIC };
+    let config = Config { difficulty: Difficulty::with_limits(1000, 1000, 10, &limits), ..Config::default() };
+    let (width, height) = config.difficulty.dimensions();
+
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let mut board = GameBoard::new();
+        board.setup(&config, &mut ModernRandomNumberGenerator::new());
+        let (row, column) = (0..board.num_rows() * board.num_columns())
+            .map(|index| (index / board.num_columns(), index % board.num_columns()))
+            .find(|&(row, column)| board.get_display(row, column) == Ok(15))
+            .expect("board has no blank squares");
+
+        let start = Instant::now();
+        let outcome = board.left_click(row, column).unwrap();
+        total += start.elapsed();
+
+        assert!(!outcome.hit_mine());
+        assert!(outcome.num_uncovered() > 0);
+        assert_ne!(board.game_state(), GameState::NotStarted);
+    }
+    println!("flood_fill {width}x{height}: {:?} per reveal over {ITERATIONS} boards", total / ITERATIONS);
+}
@@ -0,0 +1,104 @@
+//! Plays sounds through ALSA. `libasound` is loaded at runtime, like the X11 and Wayland libraries, so the
+//! game still builds without its development files and still runs, silently, on machines without it.
+
+use std::{ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_void}, ptr, sync::mpsc::{self, Receiver, Sender}, thread};
+
+use libloading::{Library, Symbol};
+
+use super::{decode_wav, AudioBackend, PlaybackError, Sound, Wav};
+
+const SND_PCM_STREAM_PLAYBACK: c_int = 0;
+/// Signed 16-bit samples in the machine's byte order, which is how [`Wav`] holds them.
+#[cfg(target_endian = "little")]
+const SND_PCM_FORMAT_S16: c_int = 2;
+#[cfg(target_endian = "big")]
+const SND_PCM_FORMAT_S16: c_int = 3;
+const SND_PCM_ACCESS_RW_INTERLEAVED: c_int = 3;
+/// How much audio ALSA may buffer, in microseconds.
+const LATENCY: c_uint = 100_000;
+
+type SndPcmOpen = unsafe extern "C" fn(*mut *mut c_void, *const c_char, c_int, c_int) -> c_int;
+type SndPcmSetParams = unsafe extern "C" fn(*mut c_void, c_int, c_int, c_uint, c_uint, c_int, c_uint) -> c_int;
+type SndPcmWritei = unsafe extern "C" fn(*mut c_void, *const c_void, c_ulong) -> c_long;
+type SndPcmRecover = unsafe extern "C" fn(*mut c_void, c_int, c_int) -> c_int;
+type SndPcm = unsafe extern "C" fn(*mut c_void) -> c_int;
+
+/// Hands sounds to a thread that plays them one after another, so that the event loop never waits on the sound card.
+pub struct AlsaBackend {
+    sender: Sender<Sound>,
+    /// Sounds the thread failed to play, waiting for [`AudioBackend::take_errors`].
+    errors: Receiver<PlaybackError>,
+}
+
+impl AlsaBackend {
+    pub fn new() -> Result<AlsaBackend, libloading::Error> {
+        let library = unsafe { Library::new("libasound.so.2") }?;
+        //Look everything up now, so that a broken library is reported here rather than on the first sound.
+        unsafe {
+            library.get::<SndPcmOpen>(b"snd_pcm_open\0")?;
+            library.get::<SndPcmSetParams>(b"snd_pcm_set_params\0")?;
+            library.get::<SndPcmWritei>(b"snd_pcm_writei\0")?;
+            library.get::<SndPcmRecover>(b"snd_pcm_recover\0")?;
+            library.get::<SndPcm>(b"snd_pcm_drain\0")?;
+            library.get::<SndPcm>(b"snd_pcm_close\0")?;
+        }
+        let (sender, receiver) = mpsc::channel::<Sound>();
+        let (error_sender, errors) = mpsc::channel();
+        thread::spawn(move || {
+            for sound in receiver {
+                let Ok(wav) = decode_wav(sound.wav()) else { continue };
+                if let Err(e) = unsafe { play(&library, &wav) } {
+                    let _ = error_sender.send(PlaybackError { message: format!("ALSA error {e}") });
+                }
+            }
+        });
+        Ok(AlsaBackend { sender, errors })
+    }
+}
+
+impl AudioBackend for AlsaBackend {
+    fn play(&mut self, sound: Sound) {
+        //The thread only stops if it panics, and then there is nothing left to play the sound.
+        let _ = self.sender.send(sound);
+    }
+
+    fn take_errors(&mut self) -> Vec<PlaybackError> {
+        self.errors.try_iter().collect()
+    }
+}
+
+/// Plays `wav` on the default device and waits for it to finish, returning the ALSA error code on failure.
+unsafe fn play(library: &Library, wav: &Wav) -> Result<(), c_int> {
+    let open: Symbol<SndPcmOpen> = library.get(b"snd_pcm_open\0").or(Err(-1))?;
+    let set_params: Symbol<SndPcmSetParams> = library.get(b"snd_pcm_set_params\0").or(Err(-1))?;
+    let writei: Symbol<SndPcmWritei> = library.get(b"snd_pcm_writei\0").or(Err(-1))?;
+    let recover: Symbol<SndPcmRecover> = library.get(b"snd_pcm_recover\0").or(Err(-1))?;
+    let drain: Symbol<SndPcm> = library.get(b"snd_pcm_drain\0").or(Err(-1))?;
+    let close: Symbol<SndPcm> = library.get(b"snd_pcm_close\0").or(Err(-1))?;
+
+    let mut pcm = ptr::null_mut();
+    let result = open(&mut pcm, c"default".as_ptr(), SND_PCM_STREAM_PLAYBACK, 0);
+    if result < 0 {
+        return Err(result);
+    }
+    let result = set_params(pcm, SND_PCM_FORMAT_S16, SND_PCM_ACCESS_RW_INTERLEAVED, wav.channels as c_uint, wav.sample_rate, 1, LATENCY);
+    if result < 0 {
+        close(pcm);
+        return Err(result);
+    }
+    let mut frames_written = 0;
+    while frames_written < wav.num_frames() {
+        let remaining = &wav.samples[frames_written * wav.channels as usize..];
+        let result = writei(pcm, remaining.as_ptr() as *const c_void, (wav.num_frames() - frames_written) as c_ulong);
+        if result >= 0 {
+            frames_written += result as usize;
+        } else if recover(pcm, result as c_int, 1) < 0 {
+            //An underrun can be recovered from; anything else abandons the sound.
+            close(pcm);
+            return Err(result as c_int);
+        }
+    }
+    drain(pcm);
+    close(pcm);
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Sound effects: the bundled WAV files, a decoder for them, and the backends that play them.
+//!
+//! [`Audio`] decides what to play by following the game, and hands each sound to an [`AudioBackend`].
+//! [`system_backend`] returns one that plays through the sound card, while [`NullBackend`] and
+//! [`RecordingBackend`] stay silent for headless frontends and tests.
+
+use std::{cell::RefCell, error::Error, fmt::{Display, Formatter}, rc::Rc};
+
+use crate::grid::GameState;
+
+pub use self::wav::{decode_wav, InvalidWav, Wav};
+
+mod wav;
+#[cfg(all(feature = "sound", target_os = "linux"))]
+mod alsa;
+#[cfg(all(feature = "sound", target_os = "windows"))]
+mod winmm;
+
+pub const TICK_WAV: &[u8] = include_bytes!("../../resources/tick.wav");
+pub const EXPLODE_WAV: &[u8] = include_bytes!("../../resources/explode.wav");
+pub const WIN_WAV: &[u8] = include_bytes!("../../resources/win.wav");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    /// Played each time the timer counts up.
+    Tick,
+    /// Played when a mine goes off.
+    Explode,
+    /// Played when the game is won.
+    Win,
+}
+impl Sound {
+    /// The WAV file for the sound.
+    pub fn wav(self) -> &'static [u8] {
+        match self {
+            Sound::Tick    => TICK_WAV,
+            Sound::Explode => EXPLODE_WAV,
+            Sound::Win     => WIN_WAV,
+        }
+    }
+}
+
+/// Returned by a backend for a sound that could not be played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaybackError {
+    pub message: String,
+}
+impl Display for PlaybackError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+impl Error for PlaybackError {}
+
+/// Something that can play a [`Sound`]. Playing must not block, as it is called from the event loop.
+pub trait AudioBackend {
+    fn play(&mut self, sound: Sound);
+
+    /// Takes the errors from sounds that failed to play since the last call, oldest first. Sounds may finish
+    /// playing after [`play`](Self::play) returns, so failures are collected here rather than returned by it.
+    fn take_errors(&mut self) -> Vec<PlaybackError> {
+        Vec::new()
+    }
+}
+
+/// A backend that discards every sound.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullBackend;
+impl AudioBackend for NullBackend {
+    fn play(&mut self, _sound: Sound) {}
+}
+
+/// A backend that remembers every sound instead of playing it. Clones share the same record,
+/// so keep a clone to inspect after boxing the backend into an [`Audio`].
+#[derive(Debug, Clone, Default)]
+pub struct RecordingBackend {
+    played: Rc<RefCell<Vec<Sound>>>,
+}
+impl RecordingBackend {
+    pub fn new() -> RecordingBackend {
+        RecordingBackend::default()
+    }
+
+    /// Every sound played so far, oldest first.
+    pub fn played(&self) -> Vec<Sound> {
+        self.played.borrow().clone()
+    }
+}
+impl AudioBackend for RecordingBackend {
+    fn play(&mut self, sound: Sound) {
+        self.played.borrow_mut().push(sound);
+    }
+}
+
+/// Returns a backend that plays through ALSA, or the error from loading `libasound`. Callers that can do without
+/// sound can fall back to a [`NullBackend`].
+#[cfg(all(feature = "sound", target_os = "linux"))]
+pub fn system_backend() -> Result<Box<dyn AudioBackend>, Box<dyn Error>> {
+    Ok(Box::new(alsa::AlsaBackend::new()?))
+}
+
+/// Returns a backend that plays through winmm, as the original game did.
+#[cfg(all(feature = "sound", target_os = "windows"))]
+pub fn system_backend() -> Result<Box<dyn AudioBackend>, Box<dyn Error>> {
+    Ok(Box::new(winmm::WinmmBackend))
+}
+
+/// Returns a [`NullBackend`], as there is no sound output for this platform or the `sound` feature is disabled.
+#[cfg(not(all(feature = "sound", any(target_os = "linux", target_os = "windows"))))]
+pub fn system_backend() -> Result<Box<dyn AudioBackend>, Box<dyn Error>> {
+    Ok(Box::new(NullBackend))
+}
+
+/// Plays the game's sounds when `enabled`: a tick each time the timer counts up, an explosion on a loss and
+/// a fanfare on a win.
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    pub enabled: bool,
+    game_state: GameState,
+    seconds: u32,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>, enabled: bool) -> Audio {
+        Audio { backend, enabled, game_state: GameState::NotStarted, seconds: 0 }
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        if self.enabled {
+            self.backend.play(sound);
+        }
+    }
+
+    /// Takes the errors from sounds that failed to play since the last call, for the frontend to report as it sees fit.
+    pub fn take_errors(&mut self) -> Vec<PlaybackError> {
+        self.backend.take_errors()
+    }
+
+    /// Plays whatever sound the change since the last call calls for, given the board's state and the time
+    /// on the timer. Call it whenever either may have changed.
+    pub fn update(&mut self, game_state: GameState, seconds: u32) {
+        let previous_state = std::mem::replace(&mut self.game_state, game_state);
+        let previous_seconds = std::mem::replace(&mut self.seconds, seconds);
+        match game_state {
+            GameState::Lost { .. } if !matches!(previous_state, GameState::Lost { .. }) => self.play(Sound::Explode),
+            GameState::Won if previous_state != GameState::Won => self.play(Sound::Win),
+            GameState::Playing if seconds != previous_seconds => self.play(Sound::Tick),
+            _ => {},
+        }
+    }
+}
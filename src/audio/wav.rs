@@ -0,0 +1,69 @@
+use std::{error::Error, fmt::{Display, Formatter}};
+
+/// Decoded PCM audio, interleaved when there is more than one channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wav {
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// Samples widened or kept at 16 bits, whatever the depth of the file.
+    pub samples: Vec<i16>,
+}
+impl Wav {
+    /// The number of samples per channel.
+    pub fn num_frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+}
+
+/// Returned when a WAV file is malformed or is not 8 or 16-bit PCM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidWav;
+impl Display for InvalidWav {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "invalid or unsupported WAV file")
+    }
+}
+impl Error for InvalidWav {}
+
+const WAVE_FORMAT_PCM: u16 = 1;
+
+/// Decodes an uncompressed 8 or 16-bit PCM WAV file with any number of channels.
+pub fn decode_wav(data: &[u8]) -> Result<Wav, InvalidWav> {
+    if data.get(0..4) != Some(b"RIFF") || data.get(8..12) != Some(b"WAVE") {
+        return Err(InvalidWav);
+    }
+    let mut format = None;
+    let mut offset = 12;
+    while let Some(header) = data.get(offset..offset + 8) {
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let body = data.get(offset + 8..).ok_or(InvalidWav)?;
+        //Some writers leave the size of the last chunk too large, so the data chunk is cut to what is there.
+        let body = &body[..size.min(body.len())];
+        match &header[0..4] {
+            b"fmt " => {
+                let field = |at: usize| body.get(at..at + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]])).ok_or(InvalidWav);
+                let sample_rate = body.get(4..8).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).ok_or(InvalidWav)?;
+                format = Some((field(0)?, field(2)?, sample_rate, field(14)?));
+            },
+            b"data" => {
+                let (format_tag, channels, sample_rate, bits_per_sample) = format.ok_or(InvalidWav)?;
+                if format_tag != WAVE_FORMAT_PCM || channels == 0 || sample_rate == 0 {
+                    return Err(InvalidWav);
+                }
+                let samples = match bits_per_sample {
+                    //8-bit samples are unsigned, centred on 128.
+                    8 => body.iter().map(|&sample| (sample as i16 - 128) << 8).collect(),
+                    16 => body.chunks_exact(2).map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]])).collect(),
+                    _ => return Err(InvalidWav),
+                };
+                let mut wav = Wav { channels, sample_rate, samples };
+                wav.samples.truncate(wav.num_frames() * channels as usize);
+                return Ok(wav);
+            },
+            _ => {},
+        }
+        //Chunks are padded to an even length.
+        offset = offset.saturating_add(8 + size + size % 2);
+    }
+    Err(InvalidWav)
+}
@@ -0,0 +1,23 @@
+//! Plays sounds with `PlaySound`, straight from the WAV files in memory, as the original game did.
+
+use std::ffi::c_void;
+
+use super::{AudioBackend, Sound};
+
+const SND_ASYNC: u32 = 0x0001;
+const SND_NODEFAULT: u32 = 0x0002;
+const SND_MEMORY: u32 = 0x0004;
+
+#[link(name = "winmm")]
+extern "system" {
+    fn PlaySoundA(sound: *const u8, module: *mut c_void, flags: u32) -> i32;
+}
+
+pub struct WinmmBackend;
+
+impl AudioBackend for WinmmBackend {
+    fn play(&mut self, sound: Sound) {
+        //The WAV files are static, so they outlive the asynchronous playback. A new sound cuts off the last one.
+        unsafe { PlaySoundA(sound.wav().as_ptr(), std::ptr::null_mut(), SND_ASYNC | SND_NODEFAULT | SND_MEMORY) };
+    }
+}
@@ -0,0 +1,127 @@
+//! Compact, typeable codes that identify a board by its difficulty and seed.
+//!
+//! A code looks like `e-1Z4KQ0M9XH3TA`: a difficulty prefix (`b`, `i`, `e`, or `WIDTHxHEIGHTxMINES`
+//! for a custom board), then the seed in Crockford base 32, then `-s` or `-n` if the first click was
+//! kept safe for a square or a neighbourhood. Codes are case-insensitive, and the easily confused
+//! letters `I`, `L` and `O` are read as `1`, `1` and `0`.
+
+use std::{error::Error, fmt::{Display, Formatter}, str::FromStr};
+
+use crate::config::{BoardLimits, Difficulty, DifficultyType, SafeFirstClick};
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// Enough base 32 digits for any 64 bit seed.
+const SEED_DIGITS: usize = 13;
+
+/// A difficulty, first click setting and seed that together reproduce a board through
+/// [`GameBoard::setup_with_seed`](crate::grid::GameBoard::setup_with_seed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardCode {
+    pub difficulty: Difficulty,
+    /// Moves mines away from the first square revealed, so the same seed lays out a different board for each setting.
+    pub safe_first_click: SafeFirstClick,
+    pub seed: u64,
+}
+
+/// Returned when a board code cannot be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidBoardCode;
+impl Display for InvalidBoardCode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "invalid board code")
+    }
+}
+impl Error for InvalidBoardCode {}
+
+impl BoardCode {
+    pub fn new(difficulty: Difficulty, safe_first_click: SafeFirstClick, seed: u64) -> BoardCode {
+        BoardCode { difficulty, safe_first_click, seed }
+    }
+
+    /// Creates a code for a new board at `difficulty` with a seed drawn from the thread-local RNG.
+    pub fn random(difficulty: Difficulty, safe_first_click: SafeFirstClick) -> BoardCode {
+        BoardCode { difficulty, safe_first_click, seed: rand::random() }
+    }
+
+    /// The length of the longest code for a board within `limits`, which is a custom board of the largest size.
+    pub fn max_length(limits: &BoardLimits) -> usize {
+        let custom = format!("{}x{}x{}", limits.max_width, limits.max_height, limits.max_mines());
+        custom.len() + 1 + SEED_DIGITS + 2
+    }
+
+    /// Parses a code, checking a custom board against `limits` rather than the classic ones. A custom board outside
+    /// them is refused rather than clamped, since that would be a different board from the one that was shared.
+    pub fn parse_with_limits(code: &str, limits: &BoardLimits) -> Result<BoardCode, InvalidBoardCode> {
+        let mut parts = code.trim().split('-');
+        let (Some(difficulty), Some(seed), safe_first_click, None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(InvalidBoardCode);
+        };
+        let safe_first_click = match safe_first_click.map(str::to_ascii_lowercase).as_deref() {
+            None      => SafeFirstClick::Off,
+            Some("s") => SafeFirstClick::Square,
+            Some("n") => SafeFirstClick::Neighbourhood,
+            Some(_)   => return Err(InvalidBoardCode),
+        };
+        let difficulty = match difficulty.to_ascii_lowercase().as_str() {
+            "b" => Difficulty::BEGINNER,
+            "i" => Difficulty::INTERMEDIATE,
+            "e" => Difficulty::EXPERT,
+            custom => {
+                let mut values = custom.split('x').map(|value| value.parse::<u32>().or(Err(InvalidBoardCode)));
+                let (Some(width), Some(height), Some(num_mines), None) = (values.next(), values.next(), values.next(), values.next()) else {
+                    return Err(InvalidBoardCode);
+                };
+                Difficulty::try_with_limits(width?, height?, num_mines?, limits).or(Err(InvalidBoardCode))?
+            },
+        };
+        Ok(BoardCode { difficulty, safe_first_click, seed: decode_seed(seed)? })
+    }
+}
+
+impl Display for BoardCode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.difficulty.difficulty_type() {
+            DifficultyType::Beginner     => write!(f, "b")?,
+            DifficultyType::Intermediate => write!(f, "i")?,
+            DifficultyType::Expert       => write!(f, "e")?,
+            DifficultyType::Custom       => {
+                let (width, height) = self.difficulty.dimensions();
+                write!(f, "{}x{}x{}", width, height, self.difficulty.num_mines())?
+            },
+        }
+        write!(f, "-{}", encode_seed(self.seed))?;
+        match self.safe_first_click {
+            SafeFirstClick::Off           => Ok(()),
+            SafeFirstClick::Square        => write!(f, "-s"),
+            SafeFirstClick::Neighbourhood => write!(f, "-n"),
+        }
+    }
+}
+
+impl FromStr for BoardCode {
+    type Err = InvalidBoardCode;
+    fn from_str(code: &str) -> Result<BoardCode, InvalidBoardCode> {
+        BoardCode::parse_with_limits(code, &BoardLimits::CLASSIC)
+    }
+}
+
+/// Encodes a seed as [`SEED_DIGITS`] base 32 digits, most significant first.
+fn encode_seed(seed: u64) -> String {
+    (0..SEED_DIGITS).rev().map(|digit| ALPHABET[((seed >> (digit * 5)) & 0b11111) as usize] as char).collect()
+}
+
+fn decode_seed(digits: &str) -> Result<u64, InvalidBoardCode> {
+    if digits.is_empty() || digits.len() > SEED_DIGITS {
+        return Err(InvalidBoardCode);
+    }
+    let mut seed: u64 = 0;
+    for digit in digits.chars() {
+        let value = match digit.to_ascii_uppercase() {
+            'O' => 0,
+            'I' | 'L' => 1,
+            digit => ALPHABET.iter().position(|&c| c as char == digit).ok_or(InvalidBoardCode)? as u64,
+        };
+        seed = seed.checked_mul(32).ok_or(InvalidBoardCode)? | value;
+    }
+    Ok(seed)
+}
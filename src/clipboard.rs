@@ -0,0 +1,41 @@
+//! Copies text to and pastes it from the system clipboard through the command line tools each platform provides,
+//! so that board codes can be shared without typing them out.
+
+use std::{error::Error, io::Write, process::{Command, Stdio}};
+
+/// Programs that put their standard input on the clipboard, tried in order.
+#[cfg(target_os = "windows")]
+const COPY: &[&[&str]] = &[&["clip"]];
+#[cfg(target_os = "macos")]
+const COPY: &[&[&str]] = &[&["pbcopy"]];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const COPY: &[&[&str]] = &[&["wl-copy"], &["xclip", "-selection", "clipboard"], &["xsel", "--clipboard", "--input"]];
+
+/// Programs that write the clipboard to their standard output, tried in order.
+#[cfg(target_os = "windows")]
+const PASTE: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]];
+#[cfg(target_os = "macos")]
+const PASTE: &[&[&str]] = &[&["pbpaste"]];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const PASTE: &[&[&str]] = &[&["wl-paste", "--no-newline"], &["xclip", "-selection", "clipboard", "-o"], &["xsel", "--clipboard", "--output"]];
+
+pub fn copy(text: &str) -> Result<(), Box<dyn Error>> {
+    for program in COPY {
+        let Ok(mut child) = Command::new(program[0]).args(&program[1..]).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn() else { continue };
+        child.stdin.take().ok_or("no standard input")?.write_all(text.as_bytes())?;
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    Err("no clipboard tool found".into())
+}
+
+pub fn paste() -> Result<String, Box<dyn Error>> {
+    for program in PASTE {
+        let Ok(output) = Command::new(program[0]).args(&program[1..]).stdin(Stdio::null()).stderr(Stdio::null()).output() else { continue };
+        if output.status.success() {
+            return Ok(String::from_utf8(output.stdout)?);
+        }
+    }
+    Err("no clipboard tool found".into())
+}
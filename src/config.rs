@@ -1,20 +1,24 @@
-use std::{error::Error, fs::{File, self}, fmt::Display};
+use std::{error::Error, fs, fmt::Display};
 
 use ini::Ini;
-use winit::dpi::PhysicalPosition;
 
 
 
 
+/// User preferences, persisted to `config.ini` in the platform config directory.
 #[derive(Debug)]
 pub struct Config {
     pub graphics_type:      GraphicsType,
     pub legacy_rng:         bool,
+    pub safe_first_click:   SafeFirstClick,
     pub difficulty:         Difficulty,
-    pub window_position:    PhysicalPosition<i32>,
+    pub board_limits:       BoardLimits,
+    pub window_position:    WindowPosition,
     pub sound_enabled:      bool,
     pub marks_enabled:      bool,
     pub colour_enabled:     bool,
+    pub scale:              Scale,
+    pub key_bindings:       KeyBindings,
     pub beginner_score:     HighScore,
     pub intermediate_score: HighScore,
     pub expert_score:       HighScore,
@@ -24,11 +28,15 @@ impl Default for Config {
         Config {
             graphics_type:      GraphicsType::OpenGL,
             legacy_rng:         false,
+            safe_first_click:   SafeFirstClick::Off,
             difficulty:         Difficulty::default(),
-            window_position:    PhysicalPosition::new(80, 80),
+            board_limits:       BoardLimits::default(),
+            window_position:    WindowPosition::new(80, 80),
             sound_enabled:      false,
             marks_enabled:      true,
             colour_enabled:     true,
+            scale:              Scale::Auto,
+            key_bindings:       KeyBindings::default(),
             beginner_score:     HighScore::default(),
             intermediate_score: HighScore::default(),
             expert_score:       HighScore::default(),
@@ -42,7 +50,18 @@ impl Config {
     fn load_from_ini() -> Result<Config, ()> {
         let config_file = dirs::config_dir().ok_or(())?.join("minesweeper_rs").join("config.ini");
         let ini = Ini::load_from_file(config_file).or(Err(()))?;
+        Config::from_ini(&ini)
+    }
+
+    /// Reads the settings from the contents of `config.ini`, failing if any required setting is missing or invalid.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_ini(ini: &Ini) -> Result<Config, ()> {
         let general_section = ini.general_section();
+        let board_limits = BoardLimits {
+            max_width: general_section.get("max_columns").map(str::parse).unwrap_or(Ok(BoardLimits::CLASSIC.max_width)).or(Err(()))?,
+            max_height: general_section.get("max_rows").map(str::parse).unwrap_or(Ok(BoardLimits::CLASSIC.max_height)).or(Err(()))?,
+            ..BoardLimits::CLASSIC
+        };
         
         let config = Config {
             graphics_type: match general_section.get("graphics_type").ok_or(())? {
@@ -50,6 +69,7 @@ impl Config {
                 "vulkan" => GraphicsType::Vulkan,
                 "metal" => GraphicsType::Metal,
                 "direct3d" | "directx" => GraphicsType::Direct3D,
+                "software" => GraphicsType::Software,
                 _ => return Err(()),
             },
             legacy_rng: match general_section.get("legacy_rng").ok_or(())? {
@@ -57,6 +77,12 @@ impl Config {
                 "false" | "no" | "n" => false,
                 _ => return Err(()),
             },
+            safe_first_click: match general_section.get("safe_first_click").unwrap_or("off") {
+                "off" | "none" => SafeFirstClick::Off,
+                "square" => SafeFirstClick::Square,
+                "neighbourhood" | "neighborhood" => SafeFirstClick::Neighbourhood,
+                _ => return Err(()),
+            },
             difficulty: match general_section.get("difficulty").ok_or(())? {
                 "beginner" => Difficulty::BEGINNER,
                 "intermediate" => Difficulty::INTERMEDIATE,
@@ -65,13 +91,14 @@ impl Config {
                     let grid_width: u32 = general_section.get("num_columns").ok_or(())?.parse().or(Err(()))?;
                     let grid_height: u32 = general_section.get("num_rows").ok_or(())?.parse().or(Err(()))?;
                     let num_mines: u32 = general_section.get("num_mines").ok_or(())?.parse().or(Err(()))?;
-                    Difficulty::new(grid_width, grid_height, num_mines)
+                    Difficulty::with_limits(grid_width, grid_height, num_mines, &board_limits)
                 },
             },
+            board_limits,
             window_position: {
                 let x: i32 = general_section.get("window_pos_x").ok_or(())?.parse().or(Err(()))?;
                 let y: i32 = general_section.get("window_pos_y").ok_or(())?.parse().or(Err(()))?;
-                PhysicalPosition::new(x, y)
+                WindowPosition::new(x, y)
             },
             sound_enabled: match general_section.get("sound_enabled").ok_or(())? {
                 "true" | "yes" | "y" => true,
@@ -88,6 +115,11 @@ impl Config {
                 "false" | "no" | "n" => false,
                 _ => return Err(()),
             },
+            scale: match general_section.get("scale").unwrap_or("auto") {
+                "auto" => Scale::Auto,
+                fixed => Scale::fixed(fixed.parse().or(Err(()))?).ok_or(())?,
+            },
+            key_bindings: KeyBindings::from_ini(ini),
             beginner_score: {
                 let beginner_name = general_section.get("beginner_name").ok_or(())?;
                 let beginner_time = general_section.get("beginner_time").ok_or(())?.parse::<u32>().or(Err(()))?.min(999);
@@ -108,6 +140,7 @@ impl Config {
 
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn save_to_ini(&self) -> Result<(), ()> {
         let root_config_dir = dirs::config_dir().ok_or(())?;
         let program_config_dir = root_config_dir.join("minesweeper_rs");
@@ -126,30 +159,51 @@ impl Config {
         ini.with_general_section()
         .set("graphics_type", self.graphics_type.to_string())
         .set("legacy_rng", self.legacy_rng.to_string())
+        .set("safe_first_click", self.safe_first_click.to_string())
         .set("difficulty", self.difficulty.difficulty_type.to_string())
         .set("num_rows", self.difficulty.grid_height.to_string())
         .set("num_columns", self.difficulty.grid_width.to_string())
         .set("num_mines", self.difficulty.num_mines.to_string())
+        .set("max_rows", self.board_limits.max_height.to_string())
+        .set("max_columns", self.board_limits.max_width.to_string())
         .set("window_pos_x", self.window_position.x.to_string())
         .set("window_pos_y", self.window_position.y.to_string())
         .set("sound_enabled", self.sound_enabled.to_string())
         .set("marks_enabled", self.marks_enabled.to_string())
         .set("colour_enabled", self.colour_enabled.to_string())
+        .set("scale", self.scale.to_string())
         .set("beginner_name", &self.beginner_score.name)
         .set("beginner_time", self.beginner_score.time.to_string())
         .set("intermediate_name", &self.intermediate_score.name)
         .set("intermediate_time", self.intermediate_score.time.to_string())
         .set("expert_name", &self.expert_score.name)
         .set("expert_time", self.expert_score.time.to_string());
+        self.key_bindings.write_to_ini(&mut ini);
 
         ini.write_to_file(&config_file_path).or(Err(()))
 
     }
 
+    /// Records a win in `time` seconds under `name` if it beats the best time for the current difficulty,
+    /// returning `true` if it did. Custom boards have no best time, so a win on one is never recorded.
+    pub fn record_time(&mut self, name: &str, time: u32) -> bool {
+        let high_score = match self.difficulty.difficulty_type() {
+            DifficultyType::Beginner     => &mut self.beginner_score,
+            DifficultyType::Intermediate => &mut self.intermediate_score,
+            DifficultyType::Expert       => &mut self.expert_score,
+            DifficultyType::Custom       => return false,
+        };
+        if time >= high_score.time {
+            return false;
+        }
+        *high_score = HighScore { name: String::from(name), time };
+        true
+    }
+
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DifficultyType {
     Beginner,
     Intermediate,
@@ -159,16 +213,17 @@ pub enum DifficultyType {
 impl Display for DifficultyType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = match self {
-            &DifficultyType::Beginner       => "beginner",
-            &DifficultyType::Intermediate   => "intermediate",
-            &DifficultyType::Expert         => "expert",
-            &DifficultyType::Custom         => "custom",
+            DifficultyType::Beginner       => "beginner",
+            DifficultyType::Intermediate   => "intermediate",
+            DifficultyType::Expert         => "expert",
+            DifficultyType::Custom         => "custom",
         };
         write!(f, "{name}")
     }
 }
 
-#[derive(Debug)]
+/// The size of the minefield and the number of mines hidden in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Difficulty {
     difficulty_type:    DifficultyType,
     num_mines:          u32,
@@ -176,10 +231,11 @@ pub struct Difficulty {
     grid_height:        u32,
 }
 impl Difficulty {
-    const BEGINNER:     Difficulty = Difficulty { difficulty_type: DifficultyType::Beginner, num_mines: 10, grid_width: 9,  grid_height: 9  };
-    const INTERMEDIATE: Difficulty = Difficulty { difficulty_type: DifficultyType::Intermediate, num_mines: 40, grid_width: 16, grid_height: 16 };
-    const EXPERT:       Difficulty = Difficulty { difficulty_type: DifficultyType::Beginner, num_mines: 99, grid_width: 30, grid_height: 16 };
+    pub const BEGINNER:     Difficulty = Difficulty { difficulty_type: DifficultyType::Beginner, num_mines: 10, grid_width: 9,  grid_height: 9  };
+    pub const INTERMEDIATE: Difficulty = Difficulty { difficulty_type: DifficultyType::Intermediate, num_mines: 40, grid_width: 16, grid_height: 16 };
+    pub const EXPERT:       Difficulty = Difficulty { difficulty_type: DifficultyType::Expert, num_mines: 99, grid_width: 30, grid_height: 16 };
 
+    /// Returns the board size as `(width, height)`, i.e. `(columns, rows)`.
     pub fn dimensions(&self) -> (u32, u32) {
         (self.grid_width, self.grid_height)
     }
@@ -188,12 +244,84 @@ impl Difficulty {
         self.num_mines
     }
 
+    pub fn difficulty_type(&self) -> DifficultyType {
+        self.difficulty_type
+    }
+
+    /// Creates a custom difficulty, clamping the values to the limits of the classic game.
     pub fn new(grid_width: u32, grid_height: u32, num_mines: u32) -> Difficulty {
-        let grid_width = grid_width.clamp(9, 30);
-        let grid_height = grid_height.clamp(9, 24);
-        let num_mines = num_mines.clamp(10, (grid_height - 1) * (grid_width - 1));
+        Difficulty::with_limits(grid_width, grid_height, num_mines, &BoardLimits::CLASSIC)
+    }
+
+    /// Creates a custom difficulty, clamping the dimensions to `limits`. At most `(width - 1) * (height - 1)` mines are allowed.
+    pub fn with_limits(grid_width: u32, grid_height: u32, num_mines: u32, limits: &BoardLimits) -> Difficulty {
+        let grid_width = grid_width.max(limits.min_width).min(limits.max_width).max(1);
+        let grid_height = grid_height.max(limits.min_height).min(limits.max_height).max(1);
+        let max_mines = (grid_height - 1).saturating_mul(grid_width - 1);
+        let num_mines = num_mines.max(MIN_MINES).min(max_mines);
         Difficulty { difficulty_type: DifficultyType::Custom, num_mines, grid_width, grid_height }
     }
+
+    /// As [`Difficulty::with_limits`], but refuses values outside the limits instead of clamping them, for values typed in by the player.
+    pub fn try_with_limits(grid_width: u32, grid_height: u32, num_mines: u32, limits: &BoardLimits) -> Result<Difficulty, InvalidDifficulty> {
+        if !(limits.min_width.max(1)..=limits.max_width).contains(&grid_width) {
+            return Err(InvalidDifficulty::Width { min: limits.min_width.max(1), max: limits.max_width });
+        }
+        if !(limits.min_height.max(1)..=limits.max_height).contains(&grid_height) {
+            return Err(InvalidDifficulty::Height { min: limits.min_height.max(1), max: limits.max_height });
+        }
+        let max_mines = (grid_height - 1).saturating_mul(grid_width - 1);
+        if !(MIN_MINES..=max_mines).contains(&num_mines) {
+            return Err(InvalidDifficulty::Mines { min: MIN_MINES, max: max_mines });
+        }
+        Ok(Difficulty { difficulty_type: DifficultyType::Custom, num_mines, grid_width, grid_height })
+    }
+}
+
+/// The fewest mines a custom board may have.
+const MIN_MINES: u32 = 10;
+
+/// Returned by [`Difficulty::try_with_limits`] for the first value outside its limits, along with the range it must lie in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidDifficulty {
+    Width { min: u32, max: u32 },
+    Height { min: u32, max: u32 },
+    /// The range depends on the width and height, which are within their limits.
+    Mines { min: u32, max: u32 },
+}
+impl Display for InvalidDifficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, min, max) = match *self {
+            Self::Width { min, max }  => ("width", min, max),
+            Self::Height { min, max } => ("height", min, max),
+            Self::Mines { min, max }  => ("mines", min, max),
+        };
+        write!(f, "{name} must be {min} to {max}")
+    }
+}
+impl Error for InvalidDifficulty {}
+
+/// The smallest and largest custom boards accepted by [`Difficulty::with_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardLimits {
+    pub min_width:  u32,
+    pub max_width:  u32,
+    pub min_height: u32,
+    pub max_height: u32,
+}
+impl BoardLimits {
+    /// The limits of the original game's Custom Field dialog.
+    pub const CLASSIC: BoardLimits = BoardLimits { min_width: 9, max_width: 30, min_height: 9, max_height: 24 };
+
+    /// The most mines allowed on the largest board, which leaves one square free in every row and column but one.
+    pub fn max_mines(&self) -> u32 {
+        self.max_width.saturating_sub(1).saturating_mul(self.max_height.saturating_sub(1))
+    }
+}
+impl Default for BoardLimits {
+    fn default() -> BoardLimits {
+        Self::CLASSIC
+    }
 }
 impl Default for Difficulty {
     fn default() -> Difficulty {
@@ -204,31 +332,235 @@ impl Default for Difficulty {
 
 
 
-#[derive(Debug)]
+/// Whether mine placement is deferred until the first reveal, and which squares it keeps clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SafeFirstClick {
+    /// Mines are placed by [`GameBoard::setup`](crate::grid::GameBoard::setup), so the first click may hit one unless
+    /// the board was set up by [`GameBoard::setup_legacy`](crate::grid::GameBoard::setup_legacy).
+    #[default]
+    Off,
+    /// The first square revealed never holds a mine.
+    Square,
+    /// Neither the first square revealed nor any of its neighbours holds a mine.
+    Neighbourhood,
+}
+impl Display for SafeFirstClick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Off           => "off",
+            Self::Square        => "square",
+            Self::Neighbourhood => "neighbourhood",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GraphicsType {
     OpenGL,
     Direct3D,
     Vulkan,
     Metal,
+    /// Draws into a CPU framebuffer, for machines without a GPU.
+    Software,
 }
 impl Display for GraphicsType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = match self {
-            &Self::OpenGL   => "opengl",
-            &Self::Direct3D => "direct3d",
-            &Self::Vulkan   => "vulkan",
-            &Self::Metal    => "metal",
+            Self::OpenGL   => "opengl",
+            Self::Direct3D => "direct3d",
+            Self::Vulkan   => "vulkan",
+            Self::Metal    => "metal",
+            Self::Software => "software",
         };
         write!(f, "{name}")
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HighScore {
-    name: String,
-    time: u32,
+    pub name: String,
+    pub time: u32,
 }
 impl Default for HighScore {
     fn default() -> HighScore {
         HighScore { name: String::from("Anonymous"), time: 999 }
     }
-}
\ No newline at end of file
+}
+
+/// How many times their size the board and its sprites are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scale {
+    /// Follows the scale factor of the monitor the window is on.
+    #[default]
+    Auto,
+    /// A fixed zoom, from 1 to [`Scale::MAX`].
+    Fixed(u32),
+}
+impl Scale {
+    pub const MAX: u32 = 4;
+
+    /// A fixed zoom, or `None` if `zoom` is outside 1 to [`Scale::MAX`].
+    pub fn fixed(zoom: u32) -> Option<Scale> {
+        (1..=Scale::MAX).contains(&zoom).then_some(Scale::Fixed(zoom))
+    }
+
+    /// The zoom to draw at on a monitor with the given scale factor. Automatic scaling rounds to the nearest
+    /// whole number, so that every pixel of a sprite covers the same number of screen pixels.
+    pub fn resolve(self, scale_factor: f64) -> u32 {
+        match self {
+            Scale::Auto => (scale_factor.round() as u32).clamp(1, Scale::MAX),
+            Scale::Fixed(zoom) => zoom.clamp(1, Scale::MAX),
+        }
+    }
+
+    /// One step larger than `current`, the zoom in use, stopping at [`Scale::MAX`].
+    pub fn zoom_in(current: u32) -> Scale {
+        Scale::Fixed((current + 1).clamp(1, Scale::MAX))
+    }
+
+    /// One step smaller than `current`, the zoom in use, stopping at 1.
+    pub fn zoom_out(current: u32) -> Scale {
+        Scale::Fixed(current.saturating_sub(1).clamp(1, Scale::MAX))
+    }
+}
+impl Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto        => write!(f, "auto"),
+            Self::Fixed(zoom) => write!(f, "{zoom}"),
+        }
+    }
+}
+
+/// Something the player can do from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// Moves the cursor one square up.
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Uncovers the square under the cursor.
+    Reveal,
+    /// Flags or marks the square under the cursor.
+    Flag,
+    /// Chords on the square under the cursor.
+    Chord,
+    NewGame,
+    /// Starts a new beginner game.
+    Beginner,
+    Intermediate,
+    Expert,
+    /// Switches between the colour and black and white sprites.
+    Colour,
+    /// Draws everything one step larger.
+    ZoomIn,
+    ZoomOut,
+}
+impl KeyAction {
+    pub const ALL: [KeyAction; 14] = [
+        KeyAction::Up, KeyAction::Down, KeyAction::Left, KeyAction::Right, KeyAction::Reveal, KeyAction::Flag, KeyAction::Chord,
+        KeyAction::NewGame, KeyAction::Beginner, KeyAction::Intermediate, KeyAction::Expert, KeyAction::Colour, KeyAction::ZoomIn, KeyAction::ZoomOut,
+    ];
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Up           => &["Up", "W", "K"],
+            Self::Down         => &["Down", "S", "J"],
+            Self::Left         => &["Left", "A", "H"],
+            Self::Right        => &["Right", "D", "L"],
+            Self::Reveal       => &["Space", "Return"],
+            Self::Flag         => &["F"],
+            Self::Chord        => &["X"],
+            Self::NewGame      => &["F2"],
+            Self::Beginner     => &["Key1"],
+            Self::Intermediate => &["Key2"],
+            Self::Expert       => &["Key3"],
+            Self::Colour       => &["C"],
+            Self::ZoomIn       => &["Equals", "Plus", "NumpadAdd"],
+            Self::ZoomOut      => &["Minus", "NumpadSubtract"],
+        }
+    }
+}
+impl Display for KeyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Up           => "up",
+            Self::Down         => "down",
+            Self::Left         => "left",
+            Self::Right        => "right",
+            Self::Reveal       => "reveal",
+            Self::Flag         => "flag",
+            Self::Chord        => "chord",
+            Self::NewGame      => "new_game",
+            Self::Beginner     => "beginner",
+            Self::Intermediate => "intermediate",
+            Self::Expert       => "expert",
+            Self::Colour       => "colour",
+            Self::ZoomIn       => "zoom_in",
+            Self::ZoomOut      => "zoom_out",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The keys bound to each [`KeyAction`], stored in the `[keys]` section of `config.ini` as comma-separated lists.
+///
+/// Keys are named as winit names its virtual key codes, such as `A`, `Key1`, `F2`, `Space` or `Up`,
+/// and are matched without regard to case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindings {
+    keys: [Vec<String>; KeyAction::ALL.len()],
+}
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings { keys: KeyAction::ALL.map(|action| action.default_keys().iter().map(|&key| String::from(key)).collect()) }
+    }
+}
+impl KeyBindings {
+    pub fn keys(&self, action: KeyAction) -> &[String] {
+        &self.keys[action as usize]
+    }
+
+    pub fn set_keys(&mut self, action: KeyAction, keys: Vec<String>) {
+        self.keys[action as usize] = keys;
+    }
+
+    /// The action bound to `key`. If a key is bound to more than one, the first in [`KeyAction::ALL`] wins.
+    pub fn action_for(&self, key: &str) -> Option<KeyAction> {
+        KeyAction::ALL.into_iter().find(|&action| self.keys(action).iter().any(|bound| bound.eq_ignore_ascii_case(key)))
+    }
+
+    /// Reads the `[keys]` section of `ini`, keeping the default keys for any action it leaves out.
+    /// An empty list leaves the action unbound.
+    pub fn from_ini(ini: &Ini) -> KeyBindings {
+        let mut key_bindings = KeyBindings::default();
+        if let Some(section) = ini.section(Some("keys")) {
+            for action in KeyAction::ALL {
+                if let Some(keys) = section.get(action.to_string()) {
+                    key_bindings.set_keys(action, keys.split(',').map(str::trim).filter(|key| !key.is_empty()).map(String::from).collect());
+                }
+            }
+        }
+        key_bindings
+    }
+
+    /// Writes every binding to the `[keys]` section of `ini`.
+    pub fn write_to_ini(&self, ini: &mut Ini) {
+        for action in KeyAction::ALL {
+            ini.set_to(Some("keys"), action.to_string(), self.keys(action).join(", "));
+        }
+    }
+}
+
+/// Position of the game window on screen, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowPosition {
+    pub x: i32,
+    pub y: i32,
+}
+impl WindowPosition {
+    pub const fn new(x: i32, y: i32) -> WindowPosition {
+        WindowPosition { x, y }
+    }
+}
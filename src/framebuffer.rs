@@ -0,0 +1,69 @@
+//! The pixels the software renderer draws into before handing them to the window.
+
+use crate::resources::Image;
+
+/// A CPU-side image in softbuffer's `0x00RRGGBB` pixel format.
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u32>,
+}
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Framebuffer {
+        Framebuffer { width, height, pixels: vec![0; (width * height) as usize] }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.pixels.clear();
+        self.pixels.resize((width * height) as usize, 0);
+    }
+
+    pub fn clear(&mut self, colour: u32) {
+        self.pixels.fill(colour);
+    }
+
+    /// Fills a rectangle, clipping it to the edges of the framebuffer. Rectangles wholly outside it draw nothing,
+    /// which happens while the window is still catching up with a bigger board.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32, colour: u32) {
+        let left = x.clamp(0, self.width as i32) as u32;
+        let top = y.clamp(0, self.height as i32) as u32;
+        let right = (x + width as i32).clamp(0, self.width as i32) as u32;
+        let bottom = (y + height as i32).clamp(0, self.height as i32) as u32;
+        if left >= right || top >= bottom {
+            return;
+        }
+        for row in top..bottom {
+            let start = (row * self.width) as usize;
+            self.pixels[start + left as usize..start + right as usize].fill(colour);
+        }
+    }
+
+    /// Copies `height` full-width rows of `image` starting at row `source_y` to (`x`, `y`), drawing each pixel
+    /// as a `scale` by `scale` square and scaling the position to match. Transparent pixels are skipped and
+    /// the copy is clipped to the edges of the framebuffer.
+    pub fn blit(&mut self, x: i32, y: i32, image: &Image, source_y: u32, height: u32, scale: u32) {
+        self.blit_pixels(x, y, image, source_y, height, scale, |rgba| u32::from_be_bytes([0, rgba[0], rgba[1], rgba[2]]));
+    }
+
+    /// As [`Framebuffer::blit`], but draws every pixel that is not transparent in `colour`, for text.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_tinted(&mut self, x: i32, y: i32, image: &Image, source_y: u32, height: u32, scale: u32, colour: u32) {
+        self.blit_pixels(x, y, image, source_y, height, scale, |_| colour);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn blit_pixels(&mut self, x: i32, y: i32, image: &Image, source_y: u32, height: u32, scale: u32, colour: impl Fn(&[u8]) -> u32) {
+        let (x, y, scale) = (x * scale as i32, y * scale as i32, scale as i32);
+        for row in 0..height {
+            for column in 0..image.width {
+                let source = (((source_y + row) * image.width + column) * 4) as usize;
+                let rgba = &image.pixels[source..source + 4];
+                if rgba[3] != 0 {
+                    self.fill_rect(x + column as i32 * scale, y + row as i32 * scale, scale as u32, scale as u32, colour(rgba));
+                }
+            }
+        }
+    }
+}
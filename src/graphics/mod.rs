@@ -1 +1,56 @@
-pub mod opengl;
\ No newline at end of file
+use std::{error::Error, rc::Rc};
+
+use minesweeper_rs::{config::GraphicsType, grid::GameBoard, resources::ButtonSprite, ui::Shape};
+use winit::window::Window;
+
+pub use minesweeper_rs::layout::{Layout, CELL_SIZE};
+
+pub mod opengl;
+pub mod software;
+
+/// Frontend state that a renderer needs in addition to the board itself.
+#[derive(Debug, Clone)]
+pub struct UiState {
+    /// Draws with the colour sprites when set, and the black and white ones otherwise.
+    pub colour: bool,
+    /// The time shown on the timer.
+    pub seconds: u32,
+    pub face: ButtonSprite,
+    /// Squares drawn pressed in while a mouse button is held over them.
+    pub pressed: Vec<(usize, usize)>,
+    /// The square outlined by the keyboard cursor, once the keyboard has been used.
+    pub cursor: Option<(usize, usize)>,
+    /// The menu bar and any open menu or dialog, drawn over everything else in order.
+    pub overlay: Vec<Shape>,
+    /// How many physical pixels each pixel of the [`Layout`] covers, from [`Scale::resolve`](minesweeper_rs::config::Scale::resolve).
+    pub scale: u32,
+}
+impl Default for UiState {
+    fn default() -> UiState {
+        UiState { colour: true, seconds: 0, face: ButtonSprite::Happy, pressed: Vec::new(), cursor: None, overlay: Vec::new(), scale: 1 }
+    }
+}
+
+/// A backend that can draw the game into a window. The event loop only talks to this trait,
+/// so a new backend only needs an implementation and an arm in [`create_renderer`].
+pub trait Renderer {
+    /// Creates the renderer and any graphics API state it needs for `window`.
+    fn init(window: Rc<Window>) -> Result<Self, Box<dyn Error>> where Self: Sized;
+    /// Called whenever the inner size of the window changes.
+    fn resize(&mut self, width: u32, height: u32) -> Result<(), Box<dyn Error>>;
+    fn draw_board(&mut self, board: &GameBoard, ui_state: &UiState);
+    /// Shows the frame drawn since the last call.
+    fn present(&mut self) -> Result<(), Box<dyn Error>>;
+}
+
+/// Creates the renderer chosen by `graphics_type`, using OpenGL for the APIs that have no backend yet.
+pub fn create_renderer(graphics_type: GraphicsType, window: Rc<Window>) -> Result<Box<dyn Renderer>, Box<dyn Error>> {
+    Ok(match graphics_type {
+        GraphicsType::Software => Box::new(software::SoftwareRenderer::init(window)?),
+        GraphicsType::OpenGL => Box::new(opengl::OpenGlRenderer::init(window)?),
+        unsupported => {
+            println!("{} is not supported yet, falling back to OpenGL", unsupported);
+            Box::new(opengl::OpenGlRenderer::init(window)?)
+        },
+    })
+}
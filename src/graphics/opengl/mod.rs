@@ -1 +1,296 @@
-pub mod shader;
\ No newline at end of file
+//! Renderer that draws the board with OpenGL 3.3 through a glutin context on the game window.
+//! Every sprite is a textured quad cut from one of the sprite sheets, and everything drawn from the same sheet
+//! goes out in a single draw call, so the whole board takes one.
+
+use std::{error::Error, ffi::{c_void, CString}, mem::ManuallyDrop, num::NonZeroU32, rc::Rc};
+
+use gl::types::{GLfloat, GLsizei, GLsizeiptr};
+use glutin::{config::{ConfigSurfaceTypes, ConfigTemplate, ConfigTemplateBuilder}, context::{ContextApi, ContextAttributesBuilder, GlProfile, PossiblyCurrentContext, Version}, display::{Display, DisplayApiPreference}, prelude::{GlConfig, GlDisplay, NotCurrentGlContextSurfaceAccessor, PossiblyCurrentContextGlSurfaceAccessor}, surface::{GlSurface, Surface, SurfaceAttributes, SurfaceAttributesBuilder, SwapInterval, WindowSurface}};
+use minesweeper_rs::{grid::GameBoard, resources::{self, BlockSprite, Image, BLOCK_SIZE, BUTTON_SIZE, LED_HEIGHT, LED_WIDTH}, ui::{font::{self, GLYPH_ADVANCE, GLYPH_HEIGHT}, Shape}};
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawWindowHandle};
+use winit::window::Window;
+
+use self::{shader::ShaderProgram, texture::Texture};
+use super::{Layout, Renderer, UiState, CELL_SIZE};
+
+pub mod shader;
+pub mod texture;
+
+/// Two position and two texture coordinate components.
+const FLOATS_PER_VERTEX: usize = 4;
+
+const LIGHT_GREY: [f32; 3] = [0.75, 0.75, 0.75];
+const WHITE: [f32; 3] = [1.0, 1.0, 1.0];
+const BLACK: u32 = 0x000000;
+
+//The GL objects are only dropped once their context has been made current again, in the renderer's own drop.
+pub struct OpenGlRenderer {
+    shader_program: ManuallyDrop<ShaderProgram>,
+    colour: ManuallyDrop<SpriteSheets>,
+    monochrome: ManuallyDrop<SpriteSheets>,
+    /// A single white pixel, stretched and tinted to fill rectangles.
+    white: ManuallyDrop<Texture>,
+    /// The glyphs of [`font`], in white to be tinted.
+    font: ManuallyDrop<Texture>,
+    /// The location of the uniform that every texel is multiplied by.
+    tint: i32,
+    vao: u32,
+    vbo: u32,
+    /// Rebuilt every frame and uploaded in one go.
+    vertices: Vec<f32>,
+    surface: Surface<WindowSurface>,
+    context: PossiblyCurrentContext,
+    window: Rc<Window>,
+}
+
+impl Renderer for OpenGlRenderer {
+    fn init(window: Rc<Window>) -> Result<OpenGlRenderer, Box<dyn Error>> {
+        let raw_window_handle = window.raw_window_handle();
+        let gl_display = unsafe {
+            #[cfg(target_os = "windows")]
+            let preference = DisplayApiPreference::Wgl(Some(raw_window_handle));
+
+            #[cfg(target_os = "linux")]
+            let preference = DisplayApiPreference::Glx(Box::new(winit::platform::unix::register_xlib_error_hook));
+            Display::new(window.raw_display_handle(), preference)?
+        };
+        println!("Running on: {}", gl_display.version_string());
+        let glutin_config = unsafe { gl_display.find_configs(config_template(raw_window_handle)) }?.reduce(|accum, config| {
+            if config.num_samples() > accum.num_samples() {
+                config
+            } else {
+                accum
+            }
+        }).ok_or("No suitable OpenGL config")?;
+        println!("Picked a config with {} samples", glutin_config.num_samples());
+
+        let context_attributes = ContextAttributesBuilder::new().with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3)))).with_profile(GlProfile::Core).build(Some(raw_window_handle));
+        let not_current_gl_context = unsafe { gl_display.create_context(&glutin_config, &context_attributes)? };
+        let surface = unsafe { gl_display.create_window_surface(&glutin_config, &surface_attributes(&window))? };
+        let context = not_current_gl_context.make_current(&surface)?;
+        if let Err(res) = surface.set_swap_interval(&context, SwapInterval::Wait(NonZeroU32::new(1).unwrap())) {
+            println!("Error setting vSync: {:?}", res);
+        }
+        gl::load_with(|name| {
+            let name_cstring = CString::new(name).unwrap();
+            gl_display.get_proc_address(name_cstring.as_c_str())
+        });
+
+        let (mut vao, mut vbo) = (0, 0);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+            let stride = (FLOATS_PER_VERTEX * std::mem::size_of::<GLfloat>()) as GLsizei;
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, (2 * std::mem::size_of::<GLfloat>()) as *const c_void);
+            gl::EnableVertexAttribArray(1);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+        let shader_program = ShaderProgram::new(include_str!("shaders/vertex.glsl"), include_str!("shaders/fragment.glsl"))?;
+        shader_program.bind();
+        unsafe { gl::Uniform1i(shader_program.get_uniform_location("Atlas")?, 0) };
+        let tint = shader_program.get_uniform_location("Tint")?;
+        let colour = SpriteSheets::load(resources::BLOCKS_BMP, resources::LED_BMP, resources::BUTTON_BMP)?;
+        let monochrome = SpriteSheets::load(resources::BLOCKS_BW_BMP, resources::LED_BW_BMP, resources::BUTTON_BW_BMP)?;
+
+        let white = Texture::from_image(&Image { width: 1, height: 1, pixels: vec![255; 4] });
+        let font = Texture::from_image(&font::font_image());
+        Ok(OpenGlRenderer {
+            shader_program: ManuallyDrop::new(shader_program),
+            colour: ManuallyDrop::new(colour),
+            monochrome: ManuallyDrop::new(monochrome),
+            white: ManuallyDrop::new(white),
+            font: ManuallyDrop::new(font),
+            tint,
+            vao,
+            vbo,
+            vertices: Vec::new(),
+            surface,
+            context,
+            window,
+        })
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<(), Box<dyn Error>> {
+        if let (Some(nz_width), Some(nz_height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) {
+            self.surface.resize(&self.context, nz_width, nz_height);
+            unsafe { gl::Viewport(0, 0, width as GLsizei, height as GLsizei) };
+        }
+        Ok(())
+    }
+
+    fn draw_board(&mut self, board: &GameBoard, ui_state: &UiState) {
+        //Everything is positioned in layout pixels, and the viewport maps each one onto `scale` physical pixels.
+        let size = self.window.inner_size();
+        let viewport = (size.width as f32 / ui_state.scale as f32, size.height as f32 / ui_state.scale as f32);
+        let layout = Layout::new(board);
+        let (sheets, background) = if ui_state.colour { (&self.colour, LIGHT_GREY) } else { (&self.monochrome, WHITE) };
+        unsafe {
+            gl::ClearColor(background[0], background[1], background[2], 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            self.shader_program.bind();
+        }
+        set_tint(self.tint, 0xFFFFFF);
+
+        self.vertices.clear();
+        for row in 0..board.num_rows() {
+            for column in 0..board.num_columns() {
+                let x = layout.board.0 + (column as u32 * CELL_SIZE) as i32;
+                let y = layout.board.1 + (row as u32 * CELL_SIZE) as i32;
+                let mut sprite = BlockSprite::for_square(board, row, column).unwrap();
+                if ui_state.pressed.contains(&(row, column)) {
+                    sprite = sprite.pressed();
+                }
+                push_sprite(&mut self.vertices, viewport, (x, y), &sheets.blocks, sprite.index(), BLOCK_SIZE);
+            }
+        }
+        draw_batch(self.vao, self.vbo, &self.vertices, &sheets.blocks);
+
+        self.vertices.clear();
+        let mines_left = board.num_mines() as i32 - board.num_flags() as i32;
+        for (origin, value) in [(layout.counter, mines_left), (layout.timer, ui_state.seconds as i32)] {
+            for (digit, sprite) in resources::led_digits(value).into_iter().enumerate() {
+                let x = origin.0 + (digit as u32 * LED_WIDTH) as i32;
+                push_sprite(&mut self.vertices, viewport, (x, origin.1), &sheets.leds, sprite.index(), LED_HEIGHT);
+            }
+        }
+        draw_batch(self.vao, self.vbo, &self.vertices, &sheets.leds);
+
+        self.vertices.clear();
+        push_sprite(&mut self.vertices, viewport, layout.face, &sheets.button, ui_state.face.index(), BUTTON_SIZE);
+        draw_batch(self.vao, self.vbo, &self.vertices, &sheets.button);
+
+        if let Some(square) = ui_state.cursor {
+            self.vertices.clear();
+            for (x, y, width, height) in layout.cursor_outline(square) {
+                push_quad(&mut self.vertices, viewport, (x, y), (width, height), [0.0, 0.0, 1.0, 1.0]);
+            }
+            set_tint(self.tint, BLACK);
+            draw_batch(self.vao, self.vbo, &self.vertices, &self.white);
+        }
+
+        //Shapes overlap, so each one is drawn in turn rather than batched.
+        for shape in &ui_state.overlay {
+            self.vertices.clear();
+            let (texture, colour) = match shape {
+                Shape::Rect { rect, colour } => {
+                    push_quad(&mut self.vertices, viewport, (rect.x, rect.y), (rect.width, rect.height), [0.0, 0.0, 1.0, 1.0]);
+                    (&self.white, *colour)
+                },
+                Shape::Text { position, text, colour } => {
+                    for (index, character) in text.chars().enumerate() {
+                        let x = position.0 + (index as u32 * GLYPH_ADVANCE) as i32;
+                        push_sprite(&mut self.vertices, viewport, (x, position.1), &self.font, font::glyph_index(character), GLYPH_HEIGHT);
+                    }
+                    (&self.font, *colour)
+                },
+            };
+            set_tint(self.tint, colour);
+            draw_batch(self.vao, self.vbo, &self.vertices, texture);
+        }
+    }
+
+    fn present(&mut self) -> Result<(), Box<dyn Error>> {
+        self.surface.swap_buffers(&self.context)?;
+        Ok(())
+    }
+}
+
+impl Drop for OpenGlRenderer {
+    fn drop(&mut self) {
+        //Another renderer may have made its own context current since, and the deletes below and in the textures'
+        //drops must not land on its objects. If this context cannot be made current, its objects are leaked instead.
+        if let Err(e) = self.context.make_current(&self.surface) {
+            println!("Error making the OpenGL context current to clean up: {}", e);
+            return;
+        }
+        //Safety: each field is dropped once, here, and never used again.
+        unsafe {
+            ManuallyDrop::drop(&mut self.shader_program);
+            ManuallyDrop::drop(&mut self.colour);
+            ManuallyDrop::drop(&mut self.monochrome);
+            ManuallyDrop::drop(&mut self.white);
+            ManuallyDrop::drop(&mut self.font);
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+        }
+    }
+}
+
+/// One texture for each sprite sheet, in either colour or black and white.
+struct SpriteSheets {
+    blocks: Texture,
+    leds: Texture,
+    button: Texture,
+}
+impl SpriteSheets {
+    fn load(blocks: &[u8], leds: &[u8], button: &[u8]) -> Result<SpriteSheets, Box<dyn Error>> {
+        Ok(SpriteSheets {
+            blocks: Texture::from_image(&resources::decode_bmp(blocks)?),
+            leds: Texture::from_image(&resources::decode_bmp(leds)?),
+            button: Texture::from_image(&resources::decode_bmp(button)?),
+        })
+    }
+}
+
+/// Appends two triangles drawing sprite `index` of `sheet`, a vertical strip of sprites `sprite_height` pixels
+/// tall, with its top left corner at `position` in layout pixels. `viewport` is the size of the window in layout pixels.
+fn push_sprite(vertices: &mut Vec<f32>, viewport: (f32, f32), position: (i32, i32), sheet: &Texture, index: u32, sprite_height: u32) {
+    let (top_v, bottom_v) = ((index * sprite_height) as f32 / sheet.height as f32, ((index + 1) * sprite_height) as f32 / sheet.height as f32);
+    push_quad(vertices, viewport, position, (sheet.width, sprite_height), [0.0, top_v, 1.0, bottom_v]);
+}
+
+/// Appends two triangles covering `size` layout pixels from `position`, textured with the part of the
+/// bound texture between the texture coordinates `[left, top, right, bottom]`.
+fn push_quad(vertices: &mut Vec<f32>, viewport: (f32, f32), position: (i32, i32), size: (u32, u32), tex_rect: [f32; 4]) {
+    let to_ndc = |x: i32, y: i32| [x as f32 / viewport.0 * 2.0 - 1.0, 1.0 - y as f32 / viewport.1 * 2.0];
+    let (x, y) = position;
+    let (right, bottom) = (x + size.0 as i32, y + size.1 as i32);
+    let [left_u, top_v, right_u, bottom_v] = tex_rect;
+    for (corner, tex_coord) in [
+        ((x, y), [left_u, top_v]), ((x, bottom), [left_u, bottom_v]), ((right, y), [right_u, top_v]),
+        ((x, bottom), [left_u, bottom_v]), ((right, y), [right_u, top_v]), ((right, bottom), [right_u, bottom_v]),
+    ] {
+        vertices.extend_from_slice(&to_ndc(corner.0, corner.1));
+        vertices.extend_from_slice(&tex_coord);
+    }
+}
+
+/// Sets the colour, as `0xRRGGBB`, that the texels of everything drawn next are multiplied by.
+fn set_tint(location: i32, colour: u32) {
+    let [_, red, green, blue] = colour.to_be_bytes().map(|channel| channel as f32 / 255.0);
+    unsafe { gl::Uniform4f(location, red, green, blue, 1.0) };
+}
+
+/// Uploads `vertices` and draws them in one call, sampling from `sheet`.
+fn draw_batch(vao: u32, vbo: u32, vertices: &[f32], sheet: &Texture) {
+    unsafe {
+        sheet.bind(0);
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl::BufferData(gl::ARRAY_BUFFER, std::mem::size_of_val(vertices) as GLsizeiptr, vertices.as_ptr() as *const c_void, gl::STREAM_DRAW);
+        gl::DrawArrays(gl::TRIANGLES, 0, (vertices.len() / FLOATS_PER_VERTEX) as GLsizei);
+        gl::BindVertexArray(0);
+    }
+}
+
+fn config_template(raw_window_handle: RawWindowHandle) -> ConfigTemplate {
+    ConfigTemplateBuilder::new().with_alpha_size(8).compatible_with_native_window(raw_window_handle).with_surface_type(ConfigSurfaceTypes::WINDOW).build()
+}
+
+/// Create surface attributes for window surface.
+fn surface_attributes(window: &Window) -> SurfaceAttributes<WindowSurface> {
+    let (width, height): (u32, u32) = window.inner_size().into();
+    SurfaceAttributesBuilder::<WindowSurface>::new().build(
+        window.raw_window_handle(),
+        NonZeroU32::new(width).unwrap(),
+        NonZeroU32::new(height).unwrap(),
+    )
+}
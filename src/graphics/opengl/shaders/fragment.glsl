@@ -1,8 +1,16 @@
 #version 330 core
 
-in vec3 Colour;
+in vec2 TexCoord;
 out vec4 FragColor;
 
+uniform sampler2D Atlas;
+uniform vec4 Tint;
+
 void main() {
-    FragColor = vec4(Colour, 1.0);
+    vec4 colour = texture(Atlas, TexCoord) * Tint;
+    //Transparent pixels, such as the gaps around the strokes of text, leave whatever is underneath.
+    if (colour.a == 0.0) {
+        discard;
+    }
+    FragColor = colour;
 }
\ No newline at end of file
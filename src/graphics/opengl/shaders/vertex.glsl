@@ -1,11 +1,11 @@
 #version 330 core
 
 layout (location = 0) in vec2 inPosition;
-layout (location = 1) in vec3 inColour;
+layout (location = 1) in vec2 inTexCoord;
 
-out vec3 Colour;
+out vec2 TexCoord;
 
 void main() {
-    Colour = inColour;
+    TexCoord = inTexCoord;
     gl_Position = vec4(inPosition, 0.0, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,38 @@
+use std::ffi::c_void;
+
+use gl::types::*;
+use minesweeper_rs::resources::Image;
+
+pub struct Texture {
+    inner: u32,
+    pub width: u32,
+    pub height: u32,
+}
+impl Texture {
+    /// Uploads an RGBA image, sampled with nearest filtering so that the pixel art stays sharp.
+    pub fn from_image(image: &Image) -> Self {
+        let mut inner = 0;
+        unsafe {
+            gl::GenTextures(1, &mut inner);
+            gl::BindTexture(gl::TEXTURE_2D, inner);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA8 as GLint, image.width as GLsizei, image.height as GLsizei, 0, gl::RGBA, gl::UNSIGNED_BYTE, image.pixels.as_ptr() as *const c_void);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        Self { inner, width: image.width, height: image.height }
+    }
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.inner);
+        }
+    }
+}
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.inner) }
+    }
+}
@@ -0,0 +1,160 @@
+//! Renderer that draws the board into a CPU framebuffer and presents it through softbuffer,
+//! for machines without a GPU. It never touches glutin or OpenGL, but draws the same sprites as the OpenGL renderer.
+
+use std::{error::Error, num::{NonZeroIsize, NonZeroU32}, ptr::NonNull, rc::Rc};
+
+use minesweeper_rs::{framebuffer::Framebuffer, grid::GameBoard, resources::{self, BlockSprite, Image, BLOCK_SIZE, BUTTON_SIZE, LED_HEIGHT, LED_WIDTH}, ui::{font::{self, GLYPH_ADVANCE, GLYPH_HEIGHT}, Shape}};
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle};
+use raw_window_handle_06 as rwh_06;
+use winit::window::Window;
+
+use super::{Layout, Renderer, UiState, CELL_SIZE};
+
+const LIGHT_GREY: u32 = 0xC0C0C0;
+const WHITE: u32 = 0xFFFFFF;
+const BLACK: u32 = 0x000000;
+
+pub struct SoftwareRenderer {
+    surface: softbuffer::Surface<SoftbufferWindow, SoftbufferWindow>,
+    framebuffer: Framebuffer,
+    colour: SpriteSheets,
+    monochrome: SpriteSheets,
+    font: Image,
+}
+
+/// The decoded sprite sheets, in either colour or black and white.
+struct SpriteSheets {
+    blocks: Image,
+    leds: Image,
+    button: Image,
+}
+impl SpriteSheets {
+    fn load(blocks: &[u8], leds: &[u8], button: &[u8]) -> Result<SpriteSheets, Box<dyn Error>> {
+        Ok(SpriteSheets { blocks: resources::decode_bmp(blocks)?, leds: resources::decode_bmp(leds)?, button: resources::decode_bmp(button)? })
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn init(window: Rc<Window>) -> Result<SoftwareRenderer, Box<dyn Error>> {
+        let size = window.inner_size();
+        let window = SoftbufferWindow(window);
+        let context = softbuffer::Context::new(window.clone())?;
+        let surface = softbuffer::Surface::new(&context, window)?;
+        let colour = SpriteSheets::load(resources::BLOCKS_BMP, resources::LED_BMP, resources::BUTTON_BMP)?;
+        let monochrome = SpriteSheets::load(resources::BLOCKS_BW_BMP, resources::LED_BW_BMP, resources::BUTTON_BW_BMP)?;
+        let mut renderer = SoftwareRenderer { surface, framebuffer: Framebuffer::new(0, 0), colour, monochrome, font: font::font_image() };
+        renderer.resize(size.width, size.height)?;
+        Ok(renderer)
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<(), Box<dyn Error>> {
+        //A minimised window has no area, and softbuffer cannot present to it.
+        if let (Some(nz_width), Some(nz_height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) {
+            self.surface.resize(nz_width, nz_height)?;
+            self.framebuffer.resize(width, height);
+        }
+        Ok(())
+    }
+
+    fn draw_board(&mut self, board: &GameBoard, ui_state: &UiState) {
+        let layout = Layout::new(board);
+        let (sheets, background) = if ui_state.colour { (&self.colour, LIGHT_GREY) } else { (&self.monochrome, WHITE) };
+        let scale = ui_state.scale;
+        self.framebuffer.clear(background);
+        for row in 0..board.num_rows() {
+            for column in 0..board.num_columns() {
+                let x = layout.board.0 + (column as u32 * CELL_SIZE) as i32;
+                let y = layout.board.1 + (row as u32 * CELL_SIZE) as i32;
+                let mut sprite = BlockSprite::for_square(board, row, column).unwrap();
+                if ui_state.pressed.contains(&(row, column)) {
+                    sprite = sprite.pressed();
+                }
+                self.framebuffer.blit(x, y, &sheets.blocks, sprite.index() * BLOCK_SIZE, BLOCK_SIZE, scale);
+            }
+        }
+
+        let mines_left = board.num_mines() as i32 - board.num_flags() as i32;
+        for (origin, value) in [(layout.counter, mines_left), (layout.timer, ui_state.seconds as i32)] {
+            for (digit, sprite) in resources::led_digits(value).into_iter().enumerate() {
+                let x = origin.0 + (digit as u32 * LED_WIDTH) as i32;
+                self.framebuffer.blit(x, origin.1, &sheets.leds, sprite.index() * LED_HEIGHT, LED_HEIGHT, scale);
+            }
+        }
+        self.framebuffer.blit(layout.face.0, layout.face.1, &sheets.button, ui_state.face.index() * BUTTON_SIZE, BUTTON_SIZE, scale);
+
+        if let Some(square) = ui_state.cursor {
+            for (x, y, width, height) in layout.cursor_outline(square) {
+                self.framebuffer.fill_rect(x * scale as i32, y * scale as i32, width * scale, height * scale, BLACK);
+            }
+        }
+
+        for shape in &ui_state.overlay {
+            match shape {
+                Shape::Rect { rect, colour } => self.framebuffer.fill_rect(rect.x * scale as i32, rect.y * scale as i32, rect.width * scale, rect.height * scale, *colour),
+                Shape::Text { position, text, colour } => {
+                    for (index, character) in text.chars().enumerate() {
+                        let x = position.0 + (index as u32 * GLYPH_ADVANCE) as i32;
+                        self.framebuffer.blit_tinted(x, position.1, &self.font, font::glyph_index(character) * GLYPH_HEIGHT, GLYPH_HEIGHT, scale, *colour);
+                    }
+                },
+            }
+        }
+    }
+
+    fn present(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.framebuffer.pixels.is_empty() {
+            return Ok(());
+        }
+        let mut buffer = self.surface.buffer_mut()?;
+        buffer.copy_from_slice(&self.framebuffer.pixels);
+        buffer.present()?;
+        Ok(())
+    }
+}
+
+/// Adapts winit's raw-window-handle 0.5 handles to the 0.6 traits that softbuffer expects.
+#[derive(Clone)]
+struct SoftbufferWindow(Rc<Window>);
+
+impl rwh_06::HasWindowHandle for SoftbufferWindow {
+    fn window_handle(&self) -> Result<rwh_06::WindowHandle<'_>, rwh_06::HandleError> {
+        let unavailable = rwh_06::HandleError::Unavailable;
+        let raw: rwh_06::RawWindowHandle = match self.0.raw_window_handle() {
+            RawWindowHandle::Xlib(handle) => {
+                let mut xlib = rwh_06::XlibWindowHandle::new(handle.window);
+                xlib.visual_id = handle.visual_id;
+                xlib.into()
+            },
+            RawWindowHandle::Xcb(handle) => {
+                let mut xcb = rwh_06::XcbWindowHandle::new(NonZeroU32::new(handle.window).ok_or(unavailable)?);
+                xcb.visual_id = NonZeroU32::new(handle.visual_id);
+                xcb.into()
+            },
+            RawWindowHandle::Wayland(handle) => rwh_06::WaylandWindowHandle::new(NonNull::new(handle.surface).ok_or(unavailable)?).into(),
+            RawWindowHandle::Win32(handle) => {
+                let mut win32 = rwh_06::Win32WindowHandle::new(NonZeroIsize::new(handle.hwnd as isize).ok_or(unavailable)?);
+                win32.hinstance = NonZeroIsize::new(handle.hinstance as isize);
+                win32.into()
+            },
+            RawWindowHandle::AppKit(handle) => rwh_06::AppKitWindowHandle::new(NonNull::new(handle.ns_view).ok_or(unavailable)?).into(),
+            _ => return Err(rwh_06::HandleError::NotSupported),
+        };
+        //The handle stays valid for as long as the window it was taken from, which this struct keeps alive.
+        Ok(unsafe { rwh_06::WindowHandle::borrow_raw(raw) })
+    }
+}
+
+impl rwh_06::HasDisplayHandle for SoftbufferWindow {
+    fn display_handle(&self) -> Result<rwh_06::DisplayHandle<'_>, rwh_06::HandleError> {
+        let unavailable = rwh_06::HandleError::Unavailable;
+        let raw: rwh_06::RawDisplayHandle = match self.0.raw_display_handle() {
+            RawDisplayHandle::Xlib(handle) => rwh_06::XlibDisplayHandle::new(NonNull::new(handle.display), handle.screen).into(),
+            RawDisplayHandle::Xcb(handle) => rwh_06::XcbDisplayHandle::new(NonNull::new(handle.connection), handle.screen).into(),
+            RawDisplayHandle::Wayland(handle) => rwh_06::WaylandDisplayHandle::new(NonNull::new(handle.display).ok_or(unavailable)?).into(),
+            RawDisplayHandle::Windows(_) => rwh_06::WindowsDisplayHandle::new().into(),
+            RawDisplayHandle::AppKit(_) => rwh_06::AppKitDisplayHandle::new().into(),
+            _ => return Err(rwh_06::HandleError::NotSupported),
+        };
+        Ok(unsafe { rwh_06::DisplayHandle::borrow_raw(raw) })
+    }
+}
@@ -1,26 +1,116 @@
-use std::fmt::{Display, Formatter};
+//! The minefield and the rules for uncovering it.
 
-use crate::{config::Config, util::RandomNumberGenerator};
+use std::{error::Error, fmt::{Display, Formatter}};
 
-//use crate::{win32::get_random, pref::Preferences, utils::Measurements, graphics::{RESIZE, DISPLAY}};
+use crate::{config::{Config, SafeFirstClick}, util::{LegacyRandomNumberGenerator, RandomNumberGenerator, SeededRandomNumberGenerator}};
 
-const MAXFIELDSIZE: usize = 27 * 32;
-const VISITED: u8 = 0b00000001;
-const MINE: u8 = 0b00000010;
-const FLAGGED: u8 = 0b00000100;
-const Q_MARKED: u8 = 0b00001000;
-const EXPLODED: u8 = MINE | VISITED;
+pub const VISITED: u8 = 0b00000001;
+pub const MINE: u8 = 0b00000010;
+pub const FLAGGED: u8 = 0b00000100;
+pub const Q_MARKED: u8 = 0b00001000;
+pub const EXPLODED: u8 = MINE | VISITED;
 const DO_NOT_UNCOVER: u8 = FLAGGED | MINE | VISITED;
 pub const ADJUST: i32 = 1;
 pub const RESIZE: i32 = 2;
 pub const DISPLAY: i32 = 4;
 
+/// Returned by [`GameBoard`] accessors when a `(row, column)` lies outside the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    row: usize,
+    column: usize,
+    num_rows: usize,
+    num_columns: usize,
+}
+impl OutOfBounds {
+    /// The square that was asked for, as `(row, column)`.
+    pub fn square(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    /// The size of the board at the time, as `(rows, columns)`.
+    pub fn board_size(&self) -> (usize, usize) {
+        (self.num_rows, self.num_columns)
+    }
+}
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "square ({}, {}) is outside the {}x{} board", self.row, self.column, self.num_rows, self.num_columns)
+    }
+}
+impl Error for OutOfBounds {}
+
+/// The squares changed by a single reveal, so that frontends can redraw only what moved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevealOutcome {
+    changed: Vec<(usize, usize)>,
+    num_uncovered: usize,
+    mine_hit: Option<(usize, usize)>,
+}
+impl RevealOutcome {
+    /// Every square whose appearance changed, as `(row, column)`, in the order they were changed.
+    pub fn changed(&self) -> &[(usize, usize)] {
+        &self.changed
+    }
+
+    /// The number of squares without mines that were uncovered.
+    pub fn num_uncovered(&self) -> usize {
+        self.num_uncovered
+    }
+
+    /// The first mine that went off, if any.
+    pub fn mine_hit(&self) -> Option<(usize, usize)> {
+        self.mine_hit
+    }
+
+    pub fn hit_mine(&self) -> bool {
+        self.mine_hit.is_some()
+    }
+}
+
+/// The progress of a game on a [`GameBoard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    /// The board has been set up but no square has been uncovered yet.
+    NotStarted,
+    Playing,
+    /// Every square without a mine has been uncovered.
+    Won,
+    /// A mine was uncovered at `(row, column)`.
+    Lost { row: usize, column: usize },
+}
+impl GameState {
+    /// Returns `true` once the game has been won or lost and no further input is accepted.
+    pub fn is_over(&self) -> bool {
+        matches!(self, GameState::Won | GameState::Lost { .. })
+    }
+}
+
+/// A Minesweeper board.
+///
+/// Each square is stored as a single byte: the low nibble holds the `VISITED`, `MINE`, `FLAGGED`
+/// and `Q_MARKED` bits, and the high nibble holds the number of adjacent mines. The squares are
+/// stored row by row in `grid`, which [`setup`](Self::setup) sizes from the [`Difficulty`](crate::config::Difficulty).
+///
+/// Every method taking a `(row, column)` returns [`OutOfBounds`] if it lies outside the board. The sizes and
+/// counts can only be read from outside, since the accessors rely on them matching `grid`.
 pub struct GameBoard {
-    pub grid: [u8; MAXFIELDSIZE],
-    pub num_rows: usize,
-    pub num_columns: usize,
-    pub num_mines: usize,
-    pub num_uncovered_squares: usize,
+    grid: Vec<u8>,
+    num_rows: usize,
+    num_columns: usize,
+    num_mines: usize,
+    /// The number of squares without mines still to be uncovered.
+    num_uncovered_squares: usize,
+    /// The number of squares currently flagged, for the mine counter.
+    num_flags: usize,
+    game_state: GameState,
+    marks_enabled: bool,
+    safe_first_click: SafeFirstClick,
+    seed: Option<u64>,
+    /// Set up by [`setup_legacy`](GameBoard::setup_legacy), so a mine under the first click is moved as `winmine.exe` did.
+    relocate_first_mine: bool,
+    /// Squares drawn in random order, kept until the first reveal when mine placement is deferred.
+    deferred_mine_order: Option<Vec<usize>>,
 }
 
 impl Display for GameBoard {
@@ -30,36 +120,82 @@ impl Display for GameBoard {
                 let index = (r *  self.num_columns) + c;
                 let mut symbol: char = if self.grid[index] & MINE == MINE { 'M' }
                 else {
-                    let adj = (self.grid[index] >> 4);
+                    let adj = self.grid[index] >> 4;
                     char::from_u32(adj as u32 + 48).unwrap()
                 };
                 if symbol == '0' && self.grid[index] & VISITED == VISITED { symbol = 'X'; }
-                write!(f, "{}", symbol);
+                write!(f, "{}", symbol)?;
             }
-            write!(f, "\r\n");
+            write!(f, "\r\n")?;
         }
         write!(f, "\r\n")
     }
 }
 
+impl Default for GameBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GameBoard {
     pub fn new() -> Self {
         Self {
-            grid: [0; MAXFIELDSIZE],
+            grid: Vec::new(),
             num_rows: 0,
             num_columns: 0,
             num_mines: 0,
             num_uncovered_squares: 0,
+            num_flags: 0,
+            game_state: GameState::NotStarted,
+            marks_enabled: true,
+            safe_first_click: SafeFirstClick::Off,
+            seed: None,
+            relocate_first_mine: false,
+            deferred_mine_order: None,
         }
     }
+    /// The squares, row by row, in the format described on [`GameBoard`].
+    pub fn grid(&self) -> &[u8] {
+        &self.grid
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.num_columns
+    }
+
+    pub fn num_mines(&self) -> usize {
+        self.num_mines
+    }
+
+    /// The number of squares without mines still to be uncovered. The game is won when it reaches zero.
+    pub fn num_uncovered_squares(&self) -> usize {
+        self.num_uncovered_squares
+    }
+
+    /// The number of squares currently flagged, for the mine counter.
+    pub fn num_flags(&self) -> usize {
+        self.num_flags
+    }
+
+    /// Resets every square to covered, with no mines and no marks.
     pub fn clear(&mut self) {
         for square in self.grid.iter_mut() {
             *square = 0;
         }
     }
+    /// Starts a new game at the difficulty in `config`, placing the mines with `random_number_generator`.
+    ///
+    /// If `config.safe_first_click` is enabled the random numbers are drawn here, but the mines are
+    /// not placed until the first call to [`left_click`](Self::left_click).
+    ///
+    /// Returns `RESIZE | DISPLAY` if the board dimensions changed, or just `DISPLAY` otherwise.
     pub fn setup(&mut self, config: &Config, random_number_generator: &mut dyn RandomNumberGenerator) -> i32 {
 
-        self.clear();
         let (config_width, config_height) = config.difficulty.dimensions();
         let adjust = if config_width != self.num_columns as u32 || config_height != self.num_rows as u32 {
             RESIZE | DISPLAY
@@ -69,81 +205,185 @@ impl GameBoard {
 
         self.num_rows = config_height as usize;
         self.num_columns = config_width as usize;
+        self.grid.clear();
+        self.grid.resize(self.num_rows * self.num_columns, 0);
         self.num_mines = config.difficulty.num_mines() as usize;
         self.num_uncovered_squares = self.num_rows * self.num_columns - self.num_mines;
+        self.num_flags = 0;
+        self.game_state = GameState::NotStarted;
+        self.marks_enabled = config.marks_enabled;
+        self.safe_first_click = config.safe_first_click;
+        self.seed = None;
+        self.relocate_first_mine = false;
+        self.deferred_mine_order = None;
 
-        for _ in 0..self.num_mines {
-            let (rand_row, rand_column) = loop {
-                let rand_row = random_number_generator.random_u32(config_height);
-                let rand_column = random_number_generator.random_u32(config_width);
-                if (!self.is_mine(rand_row, rand_column)) {
-                    break (rand_row, rand_column);
-                }
-            };
-            self.set_mine(rand_row, rand_column);   
+        if self.safe_first_click != SafeFirstClick::Off {
+            //Draw enough extra squares to skip a whole neighbourhood, so that the board depends only on the
+            //generator and the first square revealed.
+            let num_squares = self.num_mines + 9;
+            self.deferred_mine_order = Some(random_number_generator.choose_mine_squares(self.num_rows, self.num_columns, num_squares));
+            return adjust;
         }
+
+        for index in random_number_generator.choose_mine_squares(self.num_rows, self.num_columns, self.num_mines) {
+            self.grid[index] |= MINE;
+        }
+        self.calculate_adjacent_mines();
+        adjust
+    }
+
+    /// Starts a new game like [`setup`](Self::setup), with the mines placed by a [`SeededRandomNumberGenerator`].
+    ///
+    /// The same seed, difficulty and `safe_first_click` setting always produce the same board.
+    pub fn setup_with_seed(&mut self, config: &Config, seed: u64) -> i32 {
+        let adjust = self.setup(config, &mut SeededRandomNumberGenerator::new(seed));
+        self.seed = Some(seed);
+        adjust
+    }
+
+    /// Starts a new game like [`setup`](Self::setup), with the mines placed by `winmine.exe`'s generator.
+    ///
+    /// As in the original game, a mine under the first square revealed is moved to the first free square,
+    /// scanning each row from the top left, so the first click never loses.
+    pub fn setup_legacy(&mut self, config: &Config, random_number_generator: &mut LegacyRandomNumberGenerator) -> i32 {
+        let adjust = self.setup(config, random_number_generator);
+        self.relocate_first_mine = true;
         adjust
     }
 
-    pub fn get_display(&mut self, row: usize, column: usize) -> usize {
-        if self.is_mine(row, column) {
-            10
+    /// Returns the seed this board was generated from, if it was set up with [`setup_with_seed`](Self::setup_with_seed).
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Enables or disables the `?` state in the [`right_click`](Self::right_click) cycle, e.g. when the option is toggled mid-game.
+    pub fn set_marks_enabled(&mut self, marks_enabled: bool) {
+        self.marks_enabled = marks_enabled;
+    }
+
+    /// Returns `false` while mine placement is still waiting for the first reveal.
+    pub fn mines_placed(&self) -> bool {
+        self.deferred_mine_order.is_none()
+    }
+
+    /// Places the mines held back by [`setup`](Self::setup), keeping the squares protected by
+    /// `safe_first_click` around `(row, column)` clear.
+    fn place_deferred_mines(&mut self, order: Vec<usize>, row: usize, column: usize) {
+        let first_square = (row * self.num_columns) + column;
+        let mut neighbours = Vec::with_capacity(8);
+        if self.safe_first_click == SafeFirstClick::Neighbourhood {
+            neighbours.extend(self.neighbours(row, column).map(|(r, c)| (r * self.num_columns) + c));
+        }
+
+        //Take squares in the order they were drawn, skipping the protected ones. On a board too dense to keep the whole
+        //neighbourhood clear, fall back to the neighbours, but never the square that was clicked.
+        let candidates = order.iter().filter(|index| **index != first_square && !neighbours.contains(index))
+            .chain(order.iter().filter(|index| neighbours.contains(index)));
+        for &index in candidates.take(self.num_mines) {
+            self.grid[index] |= MINE;
+        }
+        self.calculate_adjacent_mines();
+    }
+
+    /// Moves the mine at `(row, column)` to the first square without one, leaving it in place if every square has a mine.
+    fn relocate_mine(&mut self, row: usize, column: usize) {
+        if let Some(free_square) = self.grid.iter().position(|square| square & MINE == 0) {
+            self.grid[free_square] |= MINE;
+            *self.bits_mut(row, column) &= !MINE;
+            self.calculate_adjacent_mines();
+        }
+    }
+
+    /// Returns the sprite index for a square: `10` for a mine, `15` for a blank square, or the number of adjacent mines.
+    pub fn get_display(&self, row: usize, column: usize) -> Result<usize, OutOfBounds> {
+        let square = self.square(row, column)?;
+        if square & MINE == MINE {
+            Ok(10)
         } else{
-            let adj = self.get_adjacent(row, column);
+            let adj = square >> 4;
             if adj == 0 {
-                15
+                Ok(15)
             } else {
-                adj as usize
+                Ok(adj as usize)
             }
-        } 
+        }
     }
 
-    pub fn get_square(&mut self, row: usize, column: usize) -> &mut u8 {
-        &mut self.grid[(row * self.num_columns) + column]
+    /// Returns the position of `(row, column)` in `grid`, or an error if it lies outside the board.
+    pub fn index(&self, row: usize, column: usize) -> Result<usize, OutOfBounds> {
+        if row < self.num_rows && column < self.num_columns {
+            Ok((row * self.num_columns) + column)
+        } else {
+            Err(OutOfBounds { row, column, num_rows: self.num_rows, num_columns: self.num_columns })
+        }
+    }
+    pub fn get_square(&mut self, row: usize, column: usize) -> Result<&mut u8, OutOfBounds> {
+        let index = self.index(row, column)?;
+        Ok(&mut self.grid[index])
+    }
+    /// Returns the raw bits of a square without borrowing the board mutably.
+    pub fn square(&self, row: usize, column: usize) -> Result<u8, OutOfBounds> {
+        Ok(self.grid[self.index(row, column)?])
     }
 
-    pub fn is_visited(&mut self, row: usize, column: usize) -> bool {
-        *self.get_square(row, column) & VISITED == VISITED
+    pub fn is_visited(&self, row: usize, column: usize) -> Result<bool, OutOfBounds> {
+        self.mask_matches_exact(row, column, VISITED)
     }
-    pub fn is_mine(&mut self, row: usize, column: usize) -> bool {
-        *self.get_square(row, column) & MINE == MINE
+    pub fn is_mine(&self, row: usize, column: usize) -> Result<bool, OutOfBounds> {
+        self.mask_matches_exact(row, column, MINE)
     }
-    pub fn is_flagged(&mut self, row: usize, column: usize) -> bool {
-        *self.get_square(row, column) & FLAGGED == FLAGGED
+    pub fn is_flagged(&self, row: usize, column: usize) -> Result<bool, OutOfBounds> {
+        self.mask_matches_exact(row, column, FLAGGED)
     }
-    pub fn is_q_marked(&mut self, row: usize, column: usize) -> bool {
-        *self.get_square(row, column) & Q_MARKED == Q_MARKED
+    pub fn is_q_marked(&self, row: usize, column: usize) -> Result<bool, OutOfBounds> {
+        self.mask_matches_exact(row, column, Q_MARKED)
     }
 
-    pub fn set_visited(&mut self, row: usize, column: usize) {
-        *self.get_square(row, column) |= VISITED;
+    pub fn set_visited(&mut self, row: usize, column: usize) -> Result<(), OutOfBounds> {
+        *self.get_square(row, column)? |= VISITED;
+        Ok(())
+    }
+    pub fn set_mine(&mut self, row: usize, column: usize) -> Result<(), OutOfBounds> {
+        *self.get_square(row, column)? |= MINE;
+        Ok(())
+    }
+    pub fn set_flagged(&mut self, row: usize, column: usize) -> Result<(), OutOfBounds> {
+        *self.get_square(row, column)? |= FLAGGED;
+        Ok(())
     }
-    pub fn set_mine(&mut self, row: usize, column: usize) {
-        *self.get_square(row, column) |= MINE;
+    pub fn set_q_marked(&mut self, row: usize, column: usize) -> Result<(), OutOfBounds> {
+        *self.get_square(row, column)? |= Q_MARKED;
+        Ok(())
     }
-    pub fn set_flagged(&mut self, row: usize, column: usize) {
-        *self.get_square(row, column) |= FLAGGED;
+    pub fn clear_flagged(&mut self, row: usize, column: usize) -> Result<(), OutOfBounds> {
+        *self.get_square(row, column)? &= !FLAGGED;
+        Ok(())
     }
-    pub fn set_q_marked(&mut self, row: usize, column: usize) {
-        *self.get_square(row, column) |= Q_MARKED;
+    pub fn clear_q_marked(&mut self, row: usize, column: usize) -> Result<(), OutOfBounds> {
+        *self.get_square(row, column)? &= !Q_MARKED;
+        Ok(())
     }
 
-    pub fn get_adjacent(&mut self, row: usize, column: usize) -> u8 {
-        (*self.get_square(row, column) >> 4)
+    pub fn get_adjacent(&self, row: usize, column: usize) -> Result<u8, OutOfBounds> {
+        Ok(self.square(row, column)? >> 4)
     }
-    pub fn set_adjacent(&mut self, row: usize, column: usize, value: u8) {
+    pub fn set_adjacent(&mut self, row: usize, column: usize, value: u8) -> Result<(), OutOfBounds> {
         let value = value << 4;
-        *self.get_square(row, column) &= 0b00001111;
-        *self.get_square(row, column) |= value;
+        let square = self.get_square(row, column)?;
+        *square &= 0b00001111;
+        *square |= value;
+        Ok(())
     }
-    pub fn mask_matches_exact(&mut self, row: usize, column: usize, mask: u8) -> bool {
-        (*self.get_square(row, column) & mask) == mask
+    pub fn mask_matches_exact(&self, row: usize, column: usize, mask: u8) -> Result<bool, OutOfBounds> {
+        Ok((self.square(row, column)? & mask) == mask)
     }
-    pub fn mask_matches_any(&mut self, row: usize, column: usize, mask: u8) -> bool {
-        (*self.get_square(row, column) & mask) > 0
+    pub fn mask_matches_any(&self, row: usize, column: usize, mask: u8) -> Result<bool, OutOfBounds> {
+        Ok((self.square(row, column)? & mask) > 0)
     }
 
-    pub fn adjacent_square_indices(&mut self, row: usize, column: usize) -> Vec<(usize, usize)>{
+    /// Returns the coordinates of the up to eight squares surrounding `(row, column)`.
+    pub fn adjacent_square_indices(&self, row: usize, column: usize) -> Result<Vec<(usize, usize)>, OutOfBounds> {
+        self.index(row, column)?;
         let mut vec = Vec::with_capacity(8);
         let (up, down, left, right) = (row > 0, row < self.num_rows - 1, column > 0, column < self.num_columns - 1);
         if up {
@@ -168,12 +408,14 @@ impl GameBoard {
             }
             if right {
                 vec.push((row + 1, column + 1));
-            } 
+            }
         }
-        vec
+        Ok(vec)
     }
 
-    pub fn adjacent_square_indices_no_diag(&mut self, row: usize, column: usize) -> Vec<(usize, usize)>{
+    /// Returns the coordinates of the up to four squares orthogonally adjacent to `(row, column)`.
+    pub fn adjacent_square_indices_no_diag(&self, row: usize, column: usize) -> Result<Vec<(usize, usize)>, OutOfBounds> {
+        self.index(row, column)?;
         let mut vec = Vec::with_capacity(8);
         let (up, down, left, right) = (row > 0, row < self.num_rows - 1, column > 0, column < self.num_columns - 1);
         if up {
@@ -188,58 +430,151 @@ impl GameBoard {
         if down  {
             vec.push((row +1, column));
         }
-        vec
+        Ok(vec)
     }
 
-    pub fn left_click(&mut self, row: usize, column: usize) -> bool {
-        if self.is_visited(row, column) || self.is_flagged(row, column){
-            return false;
-        } else {
-            if self.is_mine(row, column) {
-                self.set_visited(row, column);
-                return true;
-            }
+    /// Uncovers a square, cascading through blank neighbours, and reports which squares changed.
+    ///
+    /// Does nothing once the game has been won or lost.
+    pub fn left_click(&mut self, row: usize, column: usize) -> Result<RevealOutcome, OutOfBounds> {
+        self.index(row, column)?;
+        let mut outcome = RevealOutcome::default();
+        if self.game_state.is_over() || self.has(row, column, VISITED) || self.has(row, column, FLAGGED) {
+            return Ok(outcome);
+        }
+        if let Some(order) = self.deferred_mine_order.take() {
+            self.place_deferred_mines(order, row, column);
+        }
+        if self.relocate_first_mine && self.game_state == GameState::NotStarted && self.has(row, column, MINE) {
+            self.relocate_mine(row, column);
+        }
+        self.game_state = GameState::Playing;
+        self.uncover_square(row, column, &mut outcome);
+        self.update_game_state(&mut outcome);
+        Ok(outcome)
+    }
 
-            //If it's not been visited, isn't a flag and isn't a mine, it is an empty square that should be revealed.
-            else {
-                //We need to keep a count of how many squares have been uncovered this turn.
-                let mut squares_uncovered_this_turn: usize = 0;
+    /// Uncovers a single square without checking or updating the game state, recording the result in `outcome`.
+    fn uncover_square(&mut self, row: usize, column: usize, outcome: &mut RevealOutcome) {
+        if self.has(row, column, VISITED) || self.has(row, column, FLAGGED) {
+            return;
+        }
+        outcome.changed.push((row, column));
+        *self.bits_mut(row, column) |= VISITED;
+        if self.has(row, column, MINE) {
+            outcome.mine_hit.get_or_insert((row, column));
+        }
 
-                //If this square is a numbered square (i.e. it is adjacent to at least one mine), we uncover it but no other squares.
-                if self.get_adjacent(row, column) > 0 {
-                    self.set_visited(row, column);
-                    squares_uncovered_this_turn += 1;
-                }
+        //If it's not been visited, isn't a flag and isn't a mine, it is an empty square that should be revealed.
+        else {
+            //We need to keep a count of how many squares have been uncovered this turn.
+            let mut squares_uncovered_this_turn: usize = 1;
 
-                //Otherwise, the square is 'blank'. If that is the case, we call the recursive method on it.
-                //We pass in our count, this will be recursively increased as further squares are uncovered.
-                else {
-                    self.set_visited(row, column);
-                    squares_uncovered_this_turn += 1;
-                    self.uncover_adjacent_empty_squares(row, column, &mut squares_uncovered_this_turn);
-                }
+            //If this square is a numbered square (i.e. it is adjacent to at least one mine), we uncover it but no other squares.
+            //Otherwise, the square is 'blank'. If that is the case, we flood fill outwards from it.
+            //We pass in our outcome, the changed squares will be added to it as further squares are uncovered.
+            if self.bits(row, column) >> 4 == 0 {
+                let cascade_start = outcome.changed.len();
+                self.flood_fill(row, column, &mut outcome.changed);
+                squares_uncovered_this_turn += outcome.changed.len() - cascade_start;
+            }
 
-                //Finally, subtract the number of squares uncovered this turn from the total number of squares left to uncover.
-                self.num_uncovered_squares -= squares_uncovered_this_turn;
-                println!("{} squares uncovered this turn. {} squares remaining.", squares_uncovered_this_turn, self.num_uncovered_squares);
+            //Finally, subtract the number of squares uncovered this turn from the total number of squares left to uncover.
+            self.num_uncovered_squares -= squares_uncovered_this_turn;
+            outcome.num_uncovered += squares_uncovered_this_turn;
+        }
+    }
+
+    /// Cycles a covered square through flagged, question-marked (if marks are enabled) and back to covered.
+    pub fn right_click(&mut self, row: usize, column: usize) -> Result<(), OutOfBounds> {
+        self.index(row, column)?;
+        if self.game_state.is_over() || self.has(row, column, VISITED) {
+            return Ok(());
+        }
+        let marks_enabled = self.marks_enabled;
+        let square = self.bits_mut(row, column);
+        if *square & FLAGGED == FLAGGED {
+            *square &= !FLAGGED;
+            if marks_enabled {
+                *square |= Q_MARKED;
             }
+            self.num_flags -= 1;
+        } else if *square & Q_MARKED == Q_MARKED {
+            *square &= !Q_MARKED;
+        } else {
+            *square |= FLAGGED;
+            self.num_flags += 1;
+        }
+        Ok(())
+    }
+
+    /// Chords on an uncovered numbered square: if as many neighbours are flagged as the number shows,
+    /// every other neighbour is uncovered as if it had been left-clicked.
+    pub fn chord(&mut self, row: usize, column: usize) -> Result<RevealOutcome, OutOfBounds> {
+        let mut outcome = RevealOutcome::default();
+        let adjacent_mines = self.get_adjacent(row, column)?;
+        if self.game_state.is_over() || !self.has(row, column, VISITED) || self.has(row, column, MINE) || adjacent_mines == 0 {
+            return Ok(outcome);
+        }
+        let adjacent_flags = self.neighbours(row, column).filter(|&(adj_row, adj_col)| self.has(adj_row, adj_col, FLAGGED)).count();
+        if adjacent_flags != adjacent_mines as usize {
+            return Ok(outcome);
+        }
+
+        //Every unflagged neighbour is revealed, even after a mine goes off, as the original game does.
+        for (adj_row, adj_col) in self.neighbours(row, column) {
+            self.uncover_square(adj_row, adj_col, &mut outcome);
         }
-        false
+        self.update_game_state(&mut outcome);
+        Ok(outcome)
     }
 
-    pub fn uncover_adjacent_empty_squares(&mut self, row: usize, column: usize, num_uncovered: &mut usize) {
-        for (adj_row, adj_col) in self.adjacent_square_indices(row, column) {
-            // If the adjacent square has either been visited, has a mine or a flag, do nothing.
-            if self.mask_matches_any(adj_row, adj_col, DO_NOT_UNCOVER) {
-                continue;
+    /// Ends the game after a reveal: lost if a mine went off, won if no safe squares remain.
+    /// A win flags every remaining mine, as the original game does, and adds them to `outcome`.
+    fn update_game_state(&mut self, outcome: &mut RevealOutcome) {
+        if let Some((row, column)) = outcome.mine_hit {
+            self.game_state = GameState::Lost { row, column };
+        } else if self.num_uncovered_squares == 0 {
+            self.game_state = GameState::Won;
+            for index in 0..self.num_rows * self.num_columns {
+                if self.grid[index] & MINE == MINE && self.grid[index] & FLAGGED == 0 {
+                    self.grid[index] = (self.grid[index] & !Q_MARKED) | FLAGGED;
+                    outcome.changed.push((index / self.num_columns, index % self.num_columns));
+                }
             }
-            //Otherwise, we'll mark the square as visited in any case.
-            self.set_visited(adj_row, adj_col);
-            *num_uncovered += 1;
+            self.num_flags = self.num_mines;
+        }
+    }
+
+    pub fn game_state(&self) -> GameState {
+        self.game_state
+    }
+
+    /// Uncovers every square reachable from the blank square at `(row, column)` through other blank squares,
+    /// along with the numbered squares bordering them, appending each one to `uncovered`.
+    pub fn uncover_adjacent_empty_squares(&mut self, row: usize, column: usize, uncovered: &mut Vec<(usize, usize)>) -> Result<(), OutOfBounds> {
+        self.index(row, column)?;
+        self.flood_fill(row, column, uncovered);
+        Ok(())
+    }
 
-            //If and only if the adjacent square is a blank one, we'll call this recursive method on it as well.
-            if self.get_adjacent(adj_row, adj_col) == 0 {
-                self.uncover_adjacent_empty_squares(adj_row, adj_col, num_uncovered);
+    /// The fill keeps its own stack of blank squares still to visit, so its depth does not depend on the board size.
+    fn flood_fill(&mut self, row: usize, column: usize, uncovered: &mut Vec<(usize, usize)>) {
+        let mut blank_squares = vec![(row, column)];
+        while let Some((row, column)) = blank_squares.pop() {
+            for (adj_row, adj_col) in self.neighbours(row, column) {
+                // If the adjacent square has either been visited, has a mine or a flag, do nothing.
+                if self.bits(adj_row, adj_col) & DO_NOT_UNCOVER > 0 {
+                    continue;
+                }
+                //Otherwise, we'll mark the square as visited in any case.
+                *self.bits_mut(adj_row, adj_col) |= VISITED;
+                uncovered.push((adj_row, adj_col));
+
+                //If and only if the adjacent square is a blank one, its own neighbours need uncovering as well.
+                if self.bits(adj_row, adj_col) >> 4 == 0 {
+                    blank_squares.push((adj_row, adj_col));
+                }
             }
         }
     }
@@ -247,16 +582,33 @@ impl GameBoard {
     pub fn calculate_adjacent_mines(&mut self) {
         for r in 0..self.num_rows {
             for c in 0..self.num_columns {
-                let mut mines_found = 0;
-                let adjacent_squares = self.adjacent_square_indices(r, c);
-                for (adj_r, adj_c) in adjacent_squares {
-                    if self.is_mine(adj_r, adj_c) && !self.is_mine(r, c) {
-                        mines_found += 1;
-                    }
-                }
-                self.set_adjacent(r, c, mines_found);
+                let mines_found = if self.has(r, c, MINE) {
+                    0
+                } else {
+                    self.neighbours(r, c).filter(|&(adj_r, adj_c)| self.has(adj_r, adj_c, MINE)).count() as u8
+                };
+                let square = self.bits_mut(r, c);
+                *square = (*square & 0b00001111) | (mines_found << 4);
             }
         }
-    } 
-}
+    }
 
+    /// Iterates over the squares surrounding `(row, column)` without allocating, unlike [`adjacent_square_indices`](Self::adjacent_square_indices).
+    fn neighbours(&self, row: usize, column: usize) -> impl Iterator<Item = (usize, usize)> {
+        let rows = row.saturating_sub(1)..=(row + 1).min(self.num_rows - 1);
+        let columns = column.saturating_sub(1)..=(column + 1).min(self.num_columns - 1);
+        rows.flat_map(move |r| columns.clone().map(move |c| (r, c)))
+            .filter(move |&square| square != (row, column))
+    }
+
+    //These skip the bounds check, and are only used on coordinates already known to be on the board.
+    fn bits(&self, row: usize, column: usize) -> u8 {
+        self.grid[(row * self.num_columns) + column]
+    }
+    fn bits_mut(&mut self, row: usize, column: usize) -> &mut u8 {
+        &mut self.grid[(row * self.num_columns) + column]
+    }
+    fn has(&self, row: usize, column: usize, mask: u8) -> bool {
+        self.bits(row, column) & mask == mask
+    }
+}
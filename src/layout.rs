@@ -0,0 +1,81 @@
+//! Where the parts of the game window go, in unscaled pixels, and which part the cursor is over.
+
+use crate::{grid::GameBoard, resources::{BUTTON_SIZE, LED_WIDTH}, ui::MENU_BAR_HEIGHT};
+
+/// The size in pixels of one square of the board.
+pub const CELL_SIZE: u32 = 16;
+/// The gap in pixels between the board and the edges of the window.
+const BORDER: u32 = 12;
+/// The height in pixels of the header above the board.
+const HEADER_HEIGHT: u32 = 43;
+/// The distance in pixels of the mine counter and the timer from the top of the header and from the nearer side of the window.
+const LED_INSET: (i32, i32) = (17, 16);
+/// The thickness in pixels of the outline drawn around the square with the keyboard cursor.
+const CURSOR_THICKNESS: u32 = 2;
+
+/// Where each part of the window goes for a board of a given size, in pixels from the top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub counter: (i32, i32),
+    pub timer: (i32, i32),
+    pub face: (i32, i32),
+    pub board: (i32, i32),
+    pub board_size: (u32, u32),
+    pub width: u32,
+    pub height: u32,
+}
+impl Layout {
+    pub fn new(board: &GameBoard) -> Layout {
+        let board_size = (board.num_columns() as u32 * CELL_SIZE, board.num_rows() as u32 * CELL_SIZE);
+        let width = BORDER + board_size.0 + BORDER;
+        let height = MENU_BAR_HEIGHT + BORDER + HEADER_HEIGHT + board_size.1 + BORDER;
+        let top = MENU_BAR_HEIGHT as i32;
+        Layout {
+            counter: (LED_INSET.0, top + LED_INSET.1),
+            timer: (width as i32 - LED_INSET.0 - 3 * LED_WIDTH as i32, top + LED_INSET.1),
+            face: ((width - BUTTON_SIZE) as i32 / 2, top + LED_INSET.1),
+            board: (BORDER as i32, top + (BORDER + HEADER_HEIGHT) as i32),
+            board_size,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_over_face(&self, position: (i32, i32)) -> bool {
+        contains(self.face, (BUTTON_SIZE, BUTTON_SIZE), position)
+    }
+
+    pub fn is_over_board(&self, position: (i32, i32)) -> bool {
+        contains(self.board, self.board_size, position)
+    }
+
+    /// The `(row, column)` of the square under `position`, or `None` if it is not over the board.
+    pub fn square_at(&self, position: (i32, i32)) -> Option<(usize, usize)> {
+        self.is_over_board(position).then(|| {
+            (((position.1 - self.board.1) as u32 / CELL_SIZE) as usize, ((position.0 - self.board.0) as u32 / CELL_SIZE) as usize)
+        })
+    }
+
+    /// The four rectangles, as `(x, y, width, height)`, that outline the inside of the square at `(row, column)`.
+    pub fn cursor_outline(&self, (row, column): (usize, usize)) -> [(i32, i32, u32, u32); 4] {
+        let x = self.board.0 + (column as u32 * CELL_SIZE) as i32;
+        let y = self.board.1 + (row as u32 * CELL_SIZE) as i32;
+        let far = (CELL_SIZE - CURSOR_THICKNESS) as i32;
+        [
+            (x, y, CELL_SIZE, CURSOR_THICKNESS),
+            (x, y + far, CELL_SIZE, CURSOR_THICKNESS),
+            (x, y, CURSOR_THICKNESS, CELL_SIZE),
+            (x + far, y, CURSOR_THICKNESS, CELL_SIZE),
+        ]
+    }
+}
+
+/// Converts a cursor position `(x, y)` in physical pixels to the unscaled pixels that [`Layout`] works in,
+/// when everything is drawn `scale` times its size.
+pub fn to_layout_position(position: (f64, f64), scale: u32) -> (i32, i32) {
+    ((position.0 / scale as f64).floor() as i32, (position.1 / scale as f64).floor() as i32)
+}
+
+fn contains(origin: (i32, i32), size: (u32, u32), position: (i32, i32)) -> bool {
+    (origin.0..origin.0 + size.0 as i32).contains(&position.0) && (origin.1..origin.1 + size.1 as i32).contains(&position.1)
+}
@@ -0,0 +1,36 @@
+//! Window-free Minesweeper engine.
+//!
+//! The game rules live in [`grid::GameBoard`], board sizes and user preferences in [`config`],
+//! and mine placement is driven through the [`util::RandomNumberGenerator`] trait. Seeded boards can
+//! be shared and replayed through a [`board_code::BoardCode`]. The bundled bitmaps and their decoder
+//! are in [`resources`], the sound effects in [`audio`], and the game clock in [`timer`]. The menus and
+//! dialogs in [`ui`] describe themselves as shapes for a renderer to draw, and [`layout`] says where
+//! everything goes in the window. The software renderer draws into a [`framebuffer::Framebuffer`].
+//! None of these modules depend on winit, glutin or OpenGL, so the crate can be built
+//! with `--no-default-features` and embedded in bots, tests or other frontends.
+//!
+//! ```
+//! use minesweeper_rs::{config::Config, grid::GameBoard, util::ModernRandomNumberGenerator};
+//!
+//! let config = Config::default();
+//! let mut board = GameBoard::new();
+//! board.setup(&config, &mut ModernRandomNumberGenerator::new());
+//! assert_eq!(board.num_mines(), 10);
+//! ```
+
+pub mod audio;
+pub mod board_code;
+pub mod config;
+pub mod framebuffer;
+pub mod grid;
+pub mod layout;
+pub mod resources;
+pub mod timer;
+pub mod ui;
+pub mod util;
+
+pub use board_code::BoardCode;
+pub use config::{BoardLimits, Config, Difficulty, DifficultyType, SafeFirstClick};
+pub use grid::{GameBoard, GameState, OutOfBounds, RevealOutcome};
+pub use timer::GameTimer;
+pub use util::{LegacyRandomNumberGenerator, ModernRandomNumberGenerator, RandomNumberGenerator, SeededRandomNumberGenerator};
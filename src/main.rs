@@ -1,18 +1,14 @@
 #![allow(unused)]
 
-use std::{num::NonZeroU32, ffi::{CStr, CString, c_void}};
+use std::rc::Rc;
 
-use gl::types::{GLfloat, GLsizeiptr, GLsizei};
-use glutin::{display::{Display, DisplayApiPreference}, prelude::{GlDisplay, GlConfig, NotCurrentGlContextSurfaceAccessor}, config::{ConfigTemplate, ConfigTemplateBuilder, ConfigSurfaceTypes, Config}, context::{ContextAttributesBuilder, ContextApi, Version, GlProfile, PossiblyCurrentContext}, surface::{Surface, WindowSurface, SurfaceAttributes, SurfaceAttributesBuilder, GlSurface, SwapInterval}};
-use raw_window_handle::{HasRawWindowHandle, HasRawDisplayHandle, RawWindowHandle};
-use winit::{event_loop::EventLoop, window::{WindowBuilder, Window}, event::{Event, WindowEvent}, dpi::{Size, PhysicalSize}};
+use winit::{event_loop::EventLoop, window::{Icon, Window, WindowBuilder}, event::{ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent}, dpi::{PhysicalSize, PhysicalPosition}};
 
-use crate::util::{LegacyRandomNumberGenerator, RandomNumberGenerator, ModernRandomNumberGenerator};
+use minesweeper_rs::{audio::{self, Audio, NullBackend}, config::{self, Difficulty, HighScore, KeyAction, Scale}, board_code::BoardCode, grid::{GameBoard, GameState, DISPLAY, RESIZE}, layout, resources::{self, ButtonSprite}, timer::GameTimer, ui::{menu::MenuKey, Dialog, DialogAction, MenuBar, MenuCommand, Theme}, util::LegacyRandomNumberGenerator};
 
+use graphics::{Layout, UiState};
 
-mod config;
-mod util;
-mod grid;
+mod clipboard;
 mod graphics;
 
 
@@ -20,145 +16,280 @@ mod graphics;
 fn main() {
     let mut config = config::Config::load();
 
-    let event_loop = EventLoop::new();
-    let raw_display = event_loop.raw_display_handle();
-    let window_size = PhysicalSize::new(300, 300);
-    let mut window = Some(WindowBuilder::new().with_transparent(true).with_decorations(true).with_resizable(false).with_title("Minesweeper").with_inner_size(window_size).with_position(config.window_position).build(&event_loop).unwrap());
-    let raw_window_handle = window.as_ref().map(|w| w.raw_window_handle());
-    let gl_display = unsafe {
-        #[cfg(target_os = "windows")]
-        let preference = DisplayApiPreference::Wgl(Some(raw_window_handle.unwrap()));
-        
-        #[cfg(target_os = "linux")]
-        let preference = DisplayApiPreference::Glx(Box::new(winit::platform::unix::register_xlib_error_hook));
-        Display::new(raw_display, preference).unwrap()
+    //A board code passed on the command line replays that exact board. Otherwise every new board is seeded,
+    //so its code can be shown and shared, unless the legacy generator has been asked for.
+    let mut board = GameBoard::new();
+    //Seeded once, like winmine.exe's srand() at startup, so that each legacy board continues the same sequence.
+    let mut legacy_rng = LegacyRandomNumberGenerator::new();
+    let mut board_code = match std::env::args().nth(1) {
+        Some(code) => match BoardCode::parse_with_limits(&code, &config.board_limits) {
+            Ok(board_code) => Some(board_code),
+            Err(e) => {
+                eprintln!("{code}: {e}");
+                std::process::exit(1);
+            },
+        },
+        None => next_board_code(&config),
     };
-    println!("Running on: {}", gl_display.version_string());
-    let template = config_template(raw_window_handle);
-    let glutin_config = unsafe { gl_display.find_configs(template) }.unwrap().reduce(|accum, config| {
-        if config.num_samples() > accum.num_samples() {
-            config
-        } else {
-            accum
-        }
-    }).unwrap();
-    println!("Picked a config with {} samples", glutin_config.num_samples());
-    let context_attributes = ContextAttributesBuilder::new().with_context_api(ContextApi::OpenGl(Some( Version::new(3, 3)))).with_profile(GlProfile::Core).build(raw_window_handle);
-
-    let mut not_current_gl_context = Some(
-        unsafe {
-            gl_display.create_context(&glutin_config, &context_attributes).expect("Failed to create OpenGL context")
-        }
-    );
-
-    let mut gl_state = None;
-
-
-    let vertices: [f32; 75] = [
-        // Square
-        -0.9, 0.9,      1.0, 0.0, 0.0,  // 0
-        -0.9, 0.2,      1.0, 0.0, 0.0,  // 1
-        -0.2, 0.9,      1.0, 0.0, 0.0,  // 2
-        -0.2, 0.2,      1.0, 0.0, 0.0,  // 3
-
-        // Triangle
-        0.4, 0.9,       0.0, 1.0, 0.0,  // 4
-        0.6, 0.2,       0.0, 1.0, 0.0,  // 5
-        0.2, 0.2,       0.0, 1.0, 0.0,  // 6
-
-        // Rectangle
-        0.2, -0.2,      0.0, 0.0, 1.0,  // 7
-        0.6, -0.2,      0.0, 0.0, 1.0,  // 8
-        0.2, -0.9,      0.0, 0.0, 1.0,  // 9
-        0.6, -0.9,      0.0, 0.0, 1.0,  // 10
-
-        // Diamond
-        -0.5, -0.2,     1.0, 1.0, 0.0,  // 11
-        -0.2, -0.5,     1.0, 1.0, 0.0,  // 12
-        -0.8, -0.5,     1.0, 1.0, 0.0,  // 13
-        -0.5, -0.8,     1.0, 1.0, 0.0,  // 14
-    ];
-
-    let indices = [
-        0, 1, 2, 1, 2, 3,
-        4, 5, 6,
-        7, 8, 9, 8, 9, 10,
-        11, 12, 13, 12, 13, 14,
-    ];
-
-    let (mut vbo, mut vao, mut ebo) = (0, 0, 0);
-    
-
-    let mut shader_program = None;
-
-
+    let (title, _) = new_game(&mut config, &mut board, &mut legacy_rng, board_code);
 
-
-
-    event_loop.run(move|event, event_loop_window_target, control_flow| {
+    let event_loop = EventLoop::new();
+    let window_icon = match resources::decode_ico_largest(resources::WINMINE_ICO) {
+        Ok(image) => Icon::from_rgba(image.pixels, image.width, image.height).ok(),
+        Err(e) => {
+            println!("Error decoding window icon: {}", e);
+            None
+        },
+    };
+    let window = Rc::new(WindowBuilder::new().with_transparent(true).with_decorations(true).with_resizable(false).with_title(title).with_window_icon(window_icon).with_inner_size(window_size(&board, 1)).with_position(PhysicalPosition::new(config.window_position.x, config.window_position.y)).build(&event_loop).unwrap());
+    let mut renderer = graphics::create_renderer(config.graphics_type, window.clone()).expect("Failed to create renderer");
+    let mut ui_state = UiState { colour: config.colour_enabled, scale: config.scale.resolve(window.scale_factor()), ..UiState::default() };
+    //The window was sized before its scale factor was known.
+    adjust_window(&window, &board, ui_state.scale, config.window_position, RESIZE | DISPLAY);
+    let mut timer = GameTimer::new();
+    //Kept from the last pass through the event loop, to catch the moment the game is won.
+    let mut last_game_state = board.game_state();
+    let backend = audio::system_backend().unwrap_or_else(|e| {
+        println!("Sound is unavailable: {}", e);
+        Box::new(NullBackend)
+    });
+    let mut audio = Audio::new(backend, config.sound_enabled);
+    //In layout pixels, so that it can be tested against the `Layout` whatever the scale.
+    let mut cursor_position = None;
+    let (mut left_down, mut right_down) = (false, false);
+    //The face acts when the left button is released over it, and a press on the board when any button is released.
+    let mut face_held = false;
+    let mut board_press = None;
+    let mut menu_bar = MenuBar::new(&config);
+    //While a dialog is open it takes every click and key press.
+    let mut dialog: Option<Dialog> = None;
+
+    event_loop.run(move |event, _, control_flow| {
         control_flow.set_wait();
+        //Menu items, their shortcut keys and dialog buttons all ask for things to be done here, once the event has been handled.
+        let mut command = None;
+        let mut dialog_action = None;
         match event {
-            Event::Resumed => {
-                let window = window.take().unwrap_or_else(|| {
-                    println!("Got here.");
-                    let window = WindowBuilder::new();
-                    window.build(event_loop_window_target).unwrap()
-                });
-                let gl_window = GlWindow::from_existing(&gl_display, window, &glutin_config);
-                let gl_context = not_current_gl_context.take().unwrap().make_current(&gl_window.surface).unwrap();
-                if let Err(res) = gl_window.surface.set_swap_interval(&gl_context, SwapInterval::Wait(NonZeroU32::new(1).unwrap())) {
-                    println!("Error setting vSync: {:?}", res);
-                }
-                gl::load_with(|name| {
-                    let name_cstring = CString::new(name).unwrap();
-                    let name_cstr = name_cstring.as_c_str();
-                    gl_display.get_proc_address(&name_cstr)
-                });
-                gl_state.replace(GlState { window: gl_window, context: gl_context });
-
-
-                unsafe {
-                    gl::GenVertexArrays(1, &mut vao);
-                    gl::GenBuffers(1, &mut vbo);
-                    gl::GenBuffers(1, &mut ebo);
-            
-                    gl::BindVertexArray(vao);
-            
-                    gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-                    gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * std::mem::size_of::<GLfloat>()) as GLsizeiptr, &vertices[0] as *const f32 as *const c_void, gl::STATIC_DRAW);
-            
-                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-                    gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * std::mem::size_of::<GLfloat>()) as GLsizeiptr, &indices[0] as *const i32 as *const c_void, gl::STATIC_DRAW);
-            
-                    gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 5 * std::mem::size_of::<GLfloat>() as GLsizei, std::ptr::null());
-                    gl::EnableVertexAttribArray(0);
-            
-                    gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, 5 * std::mem::size_of::<GLfloat>() as GLsizei, (2 * std::mem::size_of::<GLfloat>()) as *const c_void);
-                    gl::EnableVertexAttribArray(1);
-            
-                    gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-                    gl::BindVertexArray(0);
-                }
-                shader_program = Some(graphics::opengl::shader::ShaderProgram::new(include_str!("graphics/opengl/shaders/vertex.glsl"), include_str!("graphics/opengl/shaders/fragment.glsl")).unwrap());
-
-            },
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => control_flow.set_exit(),
-                WindowEvent::Moved(new_position) => config.window_position = new_position,
+                WindowEvent::Moved(new_position) => config.window_position = config::WindowPosition::new(new_position.x, new_position.y),
+                WindowEvent::KeyboardInput { input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(key), .. }, .. } => {
+                    if let Some(open_dialog) = &dialog {
+                        dialog_action = match key {
+                            VirtualKeyCode::Return => Some(open_dialog.default_action()),
+                            VirtualKeyCode::Escape => Some(DialogAction::Close),
+                            _ => None,
+                        };
+                    } else if menu_bar.is_open() {
+                        //An open menu takes every key as well, so nothing reaches the board behind it.
+                        let menu_key = match key {
+                            VirtualKeyCode::Up     => Some(MenuKey::Up),
+                            VirtualKeyCode::Down   => Some(MenuKey::Down),
+                            VirtualKeyCode::Left   => Some(MenuKey::Left),
+                            VirtualKeyCode::Right  => Some(MenuKey::Right),
+                            VirtualKeyCode::Return => Some(MenuKey::Choose),
+                            VirtualKeyCode::Escape => Some(MenuKey::Close),
+                            _                      => None,
+                        };
+                        if let Some(menu_key) = menu_key {
+                            command = menu_bar.key(menu_key);
+                            window.request_redraw();
+                        }
+                    } else if let Some(action) = config.key_bindings.action_for(&format!("{key:?}")) {
+                        let (row, column) = ui_state.cursor.unwrap_or((0, 0));
+                        match action {
+                            //The first key pressed for the board only brings up the cursor, in the top left corner.
+                            KeyAction::Up | KeyAction::Down | KeyAction::Left | KeyAction::Right | KeyAction::Reveal | KeyAction::Flag | KeyAction::Chord if ui_state.cursor.is_none() => {
+                                ui_state.cursor = Some((0, 0));
+                            },
+                            KeyAction::Up => ui_state.cursor = Some((row.saturating_sub(1), column)),
+                            KeyAction::Down => ui_state.cursor = Some(((row + 1).min(board.num_rows() - 1), column)),
+                            KeyAction::Left => ui_state.cursor = Some((row, column.saturating_sub(1))),
+                            KeyAction::Right => ui_state.cursor = Some((row, (column + 1).min(board.num_columns() - 1))),
+                            KeyAction::Reveal => {
+                                board.left_click(row, column).unwrap();
+                            },
+                            KeyAction::Flag => board.right_click(row, column).unwrap(),
+                            KeyAction::Chord => {
+                                board.chord(row, column).unwrap();
+                            },
+                            KeyAction::NewGame => command = Some(MenuCommand::NewGame),
+                            KeyAction::Beginner => command = Some(MenuCommand::Difficulty(Difficulty::BEGINNER)),
+                            KeyAction::Intermediate => command = Some(MenuCommand::Difficulty(Difficulty::INTERMEDIATE)),
+                            KeyAction::Expert => command = Some(MenuCommand::Difficulty(Difficulty::EXPERT)),
+                            KeyAction::Colour => command = Some(MenuCommand::ToggleColour),
+                            KeyAction::ZoomIn => command = Some(MenuCommand::Scale(Scale::zoom_in(ui_state.scale))),
+                            KeyAction::ZoomOut => command = Some(MenuCommand::Scale(Scale::zoom_out(ui_state.scale))),
+                        }
+                        window.request_redraw();
+                    }
+                },
+                WindowEvent::ReceivedCharacter(character) => {
+                    if let Some(dialog) = &mut dialog {
+                        //Ctrl+V pastes, one character at a time so that the field still takes only what it should.
+                        if character == '\u{16}' {
+                            match clipboard::paste() {
+                                Ok(text) => text.trim().chars().for_each(|character| dialog.type_character(character)),
+                                Err(e) => dialog.error = Some(format!("Cannot paste: {e}")),
+                            }
+                        } else {
+                            dialog.type_character(character);
+                        }
+                        window.request_redraw();
+                    }
+                },
+                WindowEvent::CursorMoved { position, .. } => {
+                    cursor_position = Some(layout::to_layout_position((position.x, position.y), ui_state.scale));
+                    if menu_bar.hover(Layout::new(&board).width, cursor_position) {
+                        window.request_redraw();
+                    }
+                },
+                WindowEvent::CursorLeft { .. } => cursor_position = None,
+                WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => {
+                    let layout = Layout::new(&board);
+                    if let Some(dialog) = &mut dialog {
+                        if button == MouseButton::Left {
+                            dialog.press((layout.width, layout.height), cursor_position);
+                            window.request_redraw();
+                        }
+                        return;
+                    }
+                    if menu_bar.press(layout.width, cursor_position) {
+                        window.request_redraw();
+                        return;
+                    }
+                    let over_face = cursor_position.is_some_and(|position| layout.is_over_face(position));
+                    let square = cursor_position.and_then(|position| layout.square_at(position));
+                    let playing = !board.game_state().is_over();
+                    match button {
+                        MouseButton::Left => {
+                            left_down = true;
+                            if over_face && !right_down {
+                                face_held = true;
+                            } else if playing && square.is_some() {
+                                board_press = Some(if right_down { BoardPress::Chord } else { BoardPress::Reveal });
+                            }
+                        },
+                        MouseButton::Right => {
+                            right_down = true;
+                            if left_down && board_press.is_some() {
+                                board_press = Some(BoardPress::Chord);
+                            } else if let Some((row, column)) = square.filter(|_| playing && !left_down) {
+                                //Flags go down as soon as the button does, as in the original game.
+                                board.right_click(row, column).unwrap();
+                                window.request_redraw();
+                            }
+                        },
+                        MouseButton::Middle if playing && square.is_some() => board_press = Some(BoardPress::Chord),
+                        _ => {},
+                    }
+                },
+                WindowEvent::MouseInput { state: ElementState::Released, button, .. } => {
+                    let layout = Layout::new(&board);
+                    match button {
+                        MouseButton::Left => left_down = false,
+                        MouseButton::Right => right_down = false,
+                        _ => {},
+                    }
+                    if let Some(dialog) = &mut dialog {
+                        if button == MouseButton::Left {
+                            dialog_action = dialog.release((layout.width, layout.height), cursor_position);
+                            window.request_redraw();
+                        }
+                    } else if let Some(menu_command) = menu_bar.release(layout.width, cursor_position) {
+                        command = Some(menu_command);
+                    }
+                    if button == MouseButton::Left && std::mem::take(&mut face_held) && cursor_position.is_some_and(|position| layout.is_over_face(position)) {
+                        board_code = next_board_code(&config);
+                        let (title, adjust) = new_game(&mut config, &mut board, &mut legacy_rng, board_code);
+                        window.set_title(&title);
+                        adjust_window(&window, &board, ui_state.scale, config.window_position, adjust);
+                    }
+                    //Releasing either button ends a press, so letting go of the other one afterwards does nothing.
+                    if let (Some(press), Some((row, column))) = (board_press.take(), cursor_position.and_then(|position| layout.square_at(position))) {
+                        match press {
+                            BoardPress::Reveal => board.left_click(row, column).unwrap(),
+                            BoardPress::Chord => board.chord(row, column).unwrap(),
+                        };
+                        window.request_redraw();
+                    }
+                },
+                WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                    //A fixed zoom keeps the window the same size on every monitor, rather than letting winit scale it.
+                    ui_state.scale = config.scale.resolve(scale_factor);
+                    *new_inner_size = window_size(&board, ui_state.scale);
+                    window.request_redraw();
+                },
+                WindowEvent::Resized(new_size) => {
+                    if let Err(e) = renderer.resize(new_size.width, new_size.height) {
+                        println!("Error resizing renderer: {:?}", e);
+                    }
+                    window.request_redraw();
+                },
                 _ => {},
             }
-            Event::RedrawEventsCleared => {
-                if let Some(gl_state) = &gl_state {
-                    //Render code here
-                    unsafe {
-                        gl::ClearColor(0.0, 0.0, 0.0, 1.0);
-                        gl::Clear(gl::COLOR_BUFFER_BIT);
-                        shader_program.as_ref().unwrap().bind();
-                        gl::BindVertexArray(vao);
-                        gl::DrawElements(gl::TRIANGLES, indices.len() as i32, gl::UNSIGNED_INT, std::ptr::null());
-                    }
-
-                    gl_state.window.surface.swap_buffers(&gl_state.context).unwrap();
+            Event::MainEventsCleared => {
+                let face = if face_held && cursor_position.is_some_and(|position| Layout::new(&board).is_over_face(position)) {
+                    ButtonSprite::Pressed
+                } else if board_press.is_some() {
+                    ButtonSprite::Surprised
+                } else {
+                    match board.game_state() {
+                        GameState::Won => ButtonSprite::Won,
+                        GameState::Lost { .. } => ButtonSprite::Dead,
+                        _ => ButtonSprite::Happy,
+                    }
+                };
+                if face != ui_state.face {
+                    ui_state.face = face;
+                    window.request_redraw();
+                }
+                let pressed = match (board_press, cursor_position.and_then(|position| Layout::new(&board).square_at(position))) {
+                    (Some(BoardPress::Reveal), Some(square)) => vec![square],
+                    (Some(BoardPress::Chord), Some((row, column))) => {
+                        let mut squares = board.adjacent_square_indices(row, column).unwrap();
+                        squares.push((row, column));
+                        squares
+                    },
+                    _ => Vec::new(),
+                };
+                if pressed != ui_state.pressed {
+                    ui_state.pressed = pressed;
+                    window.request_redraw();
+                }
+
+                //Keep the timer in step with the board, and wake up again when the time shown next changes.
+                timer.update(board.game_state());
+                let seconds = timer.seconds();
+                if seconds != ui_state.seconds {
+                    ui_state.seconds = seconds;
+                    window.request_redraw();
+                }
+                //A win that beats the best time for its difficulty goes into Best Times, which opens to show it.
+                if board.game_state() == GameState::Won && last_game_state != GameState::Won && config.record_time(&player_name(), seconds) {
+                    dialog = Some(Dialog::best_times(&config));
+                    window.request_redraw();
+                }
+                last_game_state = board.game_state();
+                audio.update(board.game_state(), seconds);
+                for e in audio.take_errors() {
+                    println!("Error playing sound: {}", e);
+                }
+                if let Some(next_tick) = timer.next_tick() {
+                    control_flow.set_wait_until(next_tick);
+                }
+            },
+            Event::RedrawRequested(_) => {
+                let layout = Layout::new(&board);
+                let theme = Theme::new(config.colour_enabled);
+                menu_bar.sync(&config);
+                ui_state.overlay = menu_bar.shapes(layout.width, theme);
+                if let Some(dialog) = &dialog {
+                    ui_state.overlay.extend(dialog.shapes((layout.width, layout.height), theme));
+                }
+                renderer.draw_board(&board, &ui_state);
+                if let Err(e) = renderer.present() {
+                    println!("Error presenting frame: {:?}", e);
                 }
             },
             Event::LoopDestroyed => {
@@ -166,47 +297,148 @@ fn main() {
             }
             _ => {},
         }
-    });
-
-
 
+        if let Some(action) = dialog_action {
+            match action {
+                DialogAction::Close => dialog = None,
+                DialogAction::ResetScores => {
+                    (config.beginner_score, config.intermediate_score, config.expert_score) = (HighScore::default(), HighScore::default(), HighScore::default());
+                    dialog = Some(Dialog::best_times(&config));
+                },
+                DialogAction::CustomField => {
+                    //The dialog stays open, saying what is wrong, until the values are within the limits.
+                    if let Some(difficulty) = dialog.as_mut().and_then(|dialog| dialog.custom_difficulty(&config.board_limits)) {
+                        dialog = None;
+                        command = Some(MenuCommand::Difficulty(difficulty));
+                    }
+                },
+                DialogAction::PlayBoardCode => {
+                    if let Some(entered) = dialog.as_mut().and_then(|dialog| dialog.entered_board_code(&config.board_limits)) {
+                        dialog = None;
+                        command = Some(MenuCommand::Replay(entered));
+                    }
+                },
+                DialogAction::CopyBoardCode => {
+                    if let (Some(dialog), Some(board_code)) = (&mut dialog, board_code) {
+                        dialog.error = clipboard::copy(&board_code.to_string()).err().map(|e| format!("Cannot copy: {e}"));
+                    }
+                },
+            }
+            window.request_redraw();
+        }
+        if let Some(command) = command {
+            match command {
+                MenuCommand::NewGame | MenuCommand::Difficulty(_) | MenuCommand::SafeFirstClick(_) | MenuCommand::ToggleLegacyRng | MenuCommand::Replay(_) => {
+                    //Settings that decide how the mines are laid take effect with a new game.
+                    match command {
+                        MenuCommand::Difficulty(difficulty) => config.difficulty = difficulty,
+                        MenuCommand::SafeFirstClick(safe_first_click) => config.safe_first_click = safe_first_click,
+                        MenuCommand::ToggleLegacyRng => config.legacy_rng = !config.legacy_rng,
+                        _ => {},
+                    }
+                    board_code = match command {
+                        MenuCommand::Replay(replayed) => Some(replayed),
+                        _ => next_board_code(&config),
+                    };
+                    let (title, adjust) = new_game(&mut config, &mut board, &mut legacy_rng, board_code);
+                    window.set_title(&title);
+                    adjust_window(&window, &board, ui_state.scale, config.window_position, adjust);
+                    //Keep the cursor on the board if it has shrunk.
+                    ui_state.cursor = ui_state.cursor.map(|(row, column)| (row.min(board.num_rows() - 1), column.min(board.num_columns() - 1)));
+                },
+                MenuCommand::CustomField => dialog = Some(Dialog::custom_field(&config)),
+                MenuCommand::BoardCode => dialog = Some(Dialog::board_code(board_code, &config.board_limits)),
+                MenuCommand::ToggleMarks => {
+                    config.marks_enabled = !config.marks_enabled;
+                    board.set_marks_enabled(config.marks_enabled);
+                },
+                MenuCommand::ToggleColour => {
+                    //Switches between the colour and black and white sprites, like Color in the original Game menu.
+                    config.colour_enabled = !config.colour_enabled;
+                    ui_state.colour = config.colour_enabled;
+                },
+                MenuCommand::ToggleSound => {
+                    config.sound_enabled = !config.sound_enabled;
+                    audio.enabled = config.sound_enabled;
+                },
+                MenuCommand::BestTimes => dialog = Some(Dialog::best_times(&config)),
+                MenuCommand::Exit => control_flow.set_exit(),
+                MenuCommand::Scale(scale) => {
+                    config.scale = scale;
+                    ui_state.scale = config.scale.resolve(window.scale_factor());
+                    adjust_window(&window, &board, ui_state.scale, config.window_position, RESIZE | DISPLAY);
+                },
+                //Building a second renderer for the type already in use would only tear down a working one.
+                MenuCommand::GraphicsType(graphics_type) if graphics_type == config.graphics_type => {},
+                MenuCommand::GraphicsType(graphics_type) => match graphics::create_renderer(graphics_type, window.clone()) {
+                    Ok(new_renderer) => {
+                        renderer = new_renderer;
+                        config.graphics_type = graphics_type;
+                        let size = window.inner_size();
+                        if let Err(e) = renderer.resize(size.width, size.height) {
+                            println!("Error resizing renderer: {:?}", e);
+                        }
+                    },
+                    Err(e) => println!("Error switching to {}: {}", graphics_type, e),
+                },
+                MenuCommand::About => dialog = Some(Dialog::about()),
+            }
+            window.request_redraw();
+        }
+    });
 }
 
-fn config_template(raw_window_handle: Option<RawWindowHandle>) -> ConfigTemplate {
-    let mut builder = ConfigTemplateBuilder::new().with_alpha_size(8);
-    if let Some(raw_window_handle) = raw_window_handle {
-        builder = builder.compatible_with_native_window(raw_window_handle).with_surface_type( ConfigSurfaceTypes::WINDOW );
-    }
-    builder.build()
+/// What a press on the board does when the button is released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BoardPress {
+    /// The left button alone uncovers the square.
+    Reveal,
+    /// The middle button, or the left and right together, chords on the square.
+    Chord,
+}
 
+/// A new random board code for the configured difficulty and first click setting, or `None` if the legacy generator has been asked for.
+fn next_board_code(config: &config::Config) -> Option<BoardCode> {
+    (!config.legacy_rng).then(|| BoardCode::random(config.difficulty, config.safe_first_click))
 }
 
-/// Structure to hold winit window and gl surface.
-pub struct GlWindow {
-    pub surface: Surface<WindowSurface>,
-    pub window: Window,
+/// The name a best time is kept under: the user's login name, or the default if there is none.
+fn player_name() -> String {
+    std::env::var("USERNAME").or_else(|_| std::env::var("USER")).unwrap_or_else(|_| HighScore::default().name)
 }
 
-impl GlWindow {
-    pub fn from_existing(display: &Display, window: Window, config: &Config) -> Self {
-        let attrs = surface_attributes(&window);
-        let surface = unsafe { display.create_window_surface(config, &attrs).unwrap() };
-        Self { window, surface }
+/// Sets up a new board, replaying `board_code` if there is one and drawing from `legacy_rng` otherwise,
+/// and returns the window title for it along with the flags from [`GameBoard::setup`].
+fn new_game(config: &mut config::Config, board: &mut GameBoard, legacy_rng: &mut LegacyRandomNumberGenerator, board_code: Option<BoardCode>) -> (String, i32) {
+    match board_code {
+        Some(board_code) => {
+            config.difficulty = board_code.difficulty;
+            config.safe_first_click = board_code.safe_first_click;
+            let adjust = board.setup_with_seed(config, board_code.seed);
+            (format!("Minesweeper - {board_code}"), adjust)
+        },
+        None => {
+            let adjust = board.setup_legacy(config, legacy_rng);
+            (String::from("Minesweeper"), adjust)
+        },
     }
 }
 
-/// Create surface attributes for window surface.
-pub fn surface_attributes(window: &Window) -> SurfaceAttributes<WindowSurface> {
-    let (width, height): (u32, u32) = window.inner_size().into();
-    let raw_window_handle = window.raw_window_handle();
-    SurfaceAttributesBuilder::<WindowSurface>::new().build(
-        raw_window_handle,
-        NonZeroU32::new(width).unwrap(),
-        NonZeroU32::new(height).unwrap(),
-    )
+/// The inner size of a window that fits `board` drawn at `scale`.
+fn window_size(board: &GameBoard, scale: u32) -> PhysicalSize<u32> {
+    let layout = Layout::new(board);
+    PhysicalSize::new(layout.width * scale, layout.height * scale)
 }
 
-struct GlState {
-    window: GlWindow,
-    context: PossiblyCurrentContext,
-}
\ No newline at end of file
+/// Acts on the flags from [`GameBoard::setup`]: `RESIZE` fits the window to the board at `scale`, keeping its
+/// top left corner at `window_position`, and `DISPLAY` redraws it. The renderer follows on the `Resized` event.
+fn adjust_window(window: &Window, board: &GameBoard, scale: u32, window_position: config::WindowPosition, adjust: i32) {
+    if adjust & RESIZE == RESIZE {
+        window.set_inner_size(window_size(board, scale));
+        //Some window managers move a window as it grows, so put it back where it was.
+        window.set_outer_position(PhysicalPosition::new(window_position.x, window_position.y));
+    }
+    if adjust & DISPLAY == DISPLAY {
+        window.request_redraw();
+    }
+}
@@ -0,0 +1,385 @@
+//! The bitmaps shipped in `resources/`, and a decoder that turns them into RGBA images.
+
+use std::{error::Error, fmt::{Display, Formatter}};
+
+use crate::grid::{GameBoard, GameState, OutOfBounds, EXPLODED, FLAGGED, MINE, Q_MARKED, VISITED};
+
+/// The squares of the board: 16 sprites of 16x16 stacked vertically, see [`BlockSprite`].
+pub const BLOCKS_BMP: &[u8] = include_bytes!("../resources/blocks.bmp");
+pub const BLOCKS_BW_BMP: &[u8] = include_bytes!("../resources/blocksbw.bmp");
+/// The faces of the new game button: five sprites of 24x24 stacked vertically.
+pub const BUTTON_BMP: &[u8] = include_bytes!("../resources/button.bmp");
+pub const BUTTON_BW_BMP: &[u8] = include_bytes!("../resources/buttonbw.bmp");
+/// The digits of the mine counter and timer: twelve sprites of 13x23 stacked vertically.
+pub const LED_BMP: &[u8] = include_bytes!("../resources/led.bmp");
+pub const LED_BW_BMP: &[u8] = include_bytes!("../resources/ledbw.bmp");
+pub const WINMINE_ICO: &[u8] = include_bytes!("../resources/winmine.ico");
+
+/// The size in pixels of one sprite in [`BLOCKS_BMP`].
+pub const BLOCK_SIZE: u32 = 16;
+
+/// The sprites of [`BLOCKS_BMP`], numbered from the top of the sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSprite {
+    Covered,
+    Flag,
+    QuestionMark,
+    Exploded,
+    WrongFlag,
+    Mine,
+    QuestionMarkPressed,
+    /// An uncovered square with this many adjacent mines, from 0 to 8.
+    Number(u8),
+}
+impl BlockSprite {
+    /// Chooses the sprite for a square. Once the game is lost, the remaining mines and any wrong flags are shown.
+    pub fn for_square(board: &GameBoard, row: usize, column: usize) -> Result<BlockSprite, OutOfBounds> {
+        let square = board.square(row, column)?;
+        let lost = matches!(board.game_state(), GameState::Lost { .. });
+        Ok(if square & VISITED == 0 {
+            match (square & FLAGGED != 0, square & MINE != 0) {
+                (true, false) if lost => BlockSprite::WrongFlag,
+                (true, _) => BlockSprite::Flag,
+                (false, true) if lost => BlockSprite::Mine,
+                _ if square & Q_MARKED != 0 => BlockSprite::QuestionMark,
+                _ => BlockSprite::Covered,
+            }
+        } else if square & EXPLODED == EXPLODED {
+            BlockSprite::Exploded
+        } else {
+            match board.get_display(row, column)? {
+                15 => BlockSprite::Number(0),
+                adjacent_mines => BlockSprite::Number(adjacent_mines as u8),
+            }
+        })
+    }
+
+    /// The sprite shown while a mouse button is held down over the square: covered squares look uncovered
+    /// and question marks look pressed in, while everything else is unchanged.
+    pub fn pressed(self) -> BlockSprite {
+        match self {
+            BlockSprite::Covered      => BlockSprite::Number(0),
+            BlockSprite::QuestionMark => BlockSprite::QuestionMarkPressed,
+            sprite                    => sprite,
+        }
+    }
+
+    /// The position of the sprite in the sheet. The numbers run upwards from the blank square at the bottom.
+    pub fn index(self) -> u32 {
+        match self {
+            BlockSprite::Covered             => 0,
+            BlockSprite::Flag                => 1,
+            BlockSprite::QuestionMark        => 2,
+            BlockSprite::Exploded            => 3,
+            BlockSprite::WrongFlag           => 4,
+            BlockSprite::Mine                => 5,
+            BlockSprite::QuestionMarkPressed => 6,
+            BlockSprite::Number(n)           => 15 - n.min(8) as u32,
+        }
+    }
+}
+
+/// The size in pixels of one face in [`BUTTON_BMP`].
+pub const BUTTON_SIZE: u32 = 24;
+
+/// The faces of [`BUTTON_BMP`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonSprite {
+    Pressed,
+    Won,
+    Dead,
+    /// Shown while a square is held down.
+    Surprised,
+    Happy,
+}
+impl ButtonSprite {
+    /// The position of the sprite in the sheet.
+    pub fn index(self) -> u32 {
+        match self {
+            ButtonSprite::Pressed   => 0,
+            ButtonSprite::Won       => 1,
+            ButtonSprite::Dead      => 2,
+            ButtonSprite::Surprised => 3,
+            ButtonSprite::Happy     => 4,
+        }
+    }
+}
+
+/// The size in pixels of one digit in [`LED_BMP`].
+pub const LED_WIDTH: u32 = 13;
+pub const LED_HEIGHT: u32 = 23;
+
+/// The sprites of [`LED_BMP`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedSprite {
+    Minus,
+    Blank,
+    Digit(u8),
+}
+impl LedSprite {
+    /// The position of the sprite in the sheet. The digits run upwards from 0 at the bottom.
+    pub fn index(self) -> u32 {
+        match self {
+            LedSprite::Minus    => 0,
+            LedSprite::Blank    => 1,
+            LedSprite::Digit(n) => 11 - n.min(9) as u32,
+        }
+    }
+}
+
+/// The three digits that show `value` on an LED display, with leading zeros.
+/// A negative value takes the first digit for its sign, and values are clamped to -99 and 999.
+pub fn led_digits(value: i32) -> [LedSprite; 3] {
+    let value = value.clamp(-99, 999);
+    let magnitude = value.unsigned_abs();
+    let hundreds = if value < 0 { LedSprite::Minus } else { LedSprite::Digit((magnitude / 100) as u8) };
+    [hundreds, LedSprite::Digit((magnitude / 10 % 10) as u8), LedSprite::Digit((magnitude % 10) as u8)]
+}
+
+/// A decoded image, top row first, four bytes per pixel in RGBA order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+impl Image {
+    fn set_pixel(&mut self, x: u32, y: u32, colour: [u8; 4]) {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[index..index + 4].copy_from_slice(&colour);
+    }
+}
+
+/// Returned when an image is malformed or uses a format the decoder does not support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidImage;
+impl Display for InvalidImage {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "invalid or unsupported image")
+    }
+}
+impl Error for InvalidImage {}
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+/// Far larger than any sprite sheet or icon, but small enough that a corrupt header cannot exhaust memory.
+const MAX_PIXELS: usize = 1 << 26;
+
+/// Decodes a Windows bitmap with 1, 4, 8, 24 or 32 bits per pixel, either uncompressed or RLE compressed.
+/// Pixels that an RLE bitmap skips over are left transparent.
+pub fn decode_bmp(data: &[u8]) -> Result<Image, InvalidImage> {
+    if data.get(0..2) != Some(b"BM") {
+        return Err(InvalidImage);
+    }
+    //The pixel offset in the file header counts from the start of the file, not of the DIB.
+    let pixel_offset = (read_u32(data, 10)? as usize).checked_sub(14).ok_or(InvalidImage)?;
+    decode_dib(&data[14..], Some(pixel_offset), false)
+}
+
+/// Decodes every image in a Windows icon file, in the order the file lists them.
+/// PNG images, which newer icons use for their largest sizes, are skipped.
+pub fn decode_ico(data: &[u8]) -> Result<Vec<Image>, InvalidImage> {
+    Ok(ico_entries(data)?.into_iter().map(|(_, image)| image).collect())
+}
+
+/// Decodes the largest image in a Windows icon file, preferring the most colours among images of the same size.
+pub fn decode_ico_largest(data: &[u8]) -> Result<Image, InvalidImage> {
+    ico_entries(data)?.into_iter()
+        .max_by_key(|(bit_count, image)| (image.width * image.height, *bit_count))
+        .map(|(_, image)| image)
+        .ok_or(InvalidImage)
+}
+
+/// Decodes the images of an icon file along with their colour depths.
+fn ico_entries(data: &[u8]) -> Result<Vec<(u16, Image)>, InvalidImage> {
+    if read_u16(data, 0)? != 0 || read_u16(data, 2)? != 1 {
+        return Err(InvalidImage);
+    }
+    let num_entries = read_u16(data, 4)? as usize;
+    let mut entries = Vec::with_capacity(num_entries);
+    for entry in 0..num_entries {
+        let entry_offset = 6 + entry * 16;
+        let size = read_u32(data, entry_offset + 8)? as usize;
+        let offset = read_u32(data, entry_offset + 12)? as usize;
+        let dib = data.get(offset..offset.checked_add(size).ok_or(InvalidImage)?).ok_or(InvalidImage)?;
+        if dib.starts_with(b"\x89PNG") {
+            continue;
+        }
+        entries.push((DibHeader::parse(dib)?.bit_count, decode_dib(dib, None, true)?));
+    }
+    Ok(entries)
+}
+
+/// The fields of a `BITMAPCOREHEADER` or `BITMAPINFOHEADER` that the decoder needs.
+struct DibHeader {
+    size: usize,
+    width: i32,
+    height: i32,
+    bit_count: u16,
+    compression: u32,
+    colours_used: u32,
+}
+impl DibHeader {
+    fn parse(dib: &[u8]) -> Result<DibHeader, InvalidImage> {
+        let size = read_u32(dib, 0)? as usize;
+        if size == 12 {
+            //The OS/2 header, with 16-bit dimensions and no compression.
+            return Ok(DibHeader {
+                size,
+                width: read_u16(dib, 4)? as i32,
+                height: read_u16(dib, 6)? as i32,
+                bit_count: read_u16(dib, 10)?,
+                compression: BI_RGB,
+                colours_used: 0,
+            });
+        }
+        if size < 40 {
+            return Err(InvalidImage);
+        }
+        Ok(DibHeader {
+            size,
+            width: read_u32(dib, 4)? as i32,
+            height: read_u32(dib, 8)? as i32,
+            bit_count: read_u16(dib, 14)?,
+            compression: read_u32(dib, 16)?,
+            colours_used: read_u32(dib, 32)?,
+        })
+    }
+}
+
+/// Decodes a device-independent bitmap, which starts with its header. `pixel_offset` is where the pixels start,
+/// or `None` if they follow the palette. Icons store a 1-bit transparency mask after the pixels, and their
+/// height covers both.
+fn decode_dib(dib: &[u8], pixel_offset: Option<usize>, icon: bool) -> Result<Image, InvalidImage> {
+    let header = DibHeader::parse(dib)?;
+    let height = if icon { header.height / 2 } else { header.height };
+    if header.width <= 0 || height == 0 {
+        return Err(InvalidImage);
+    }
+    //A negative height means the rows are stored top down rather than bottom up.
+    let (width, height, top_down) = (header.width as u32, height.unsigned_abs(), height < 0);
+    let num_pixels = width as usize * height as usize;
+    if num_pixels > MAX_PIXELS {
+        return Err(InvalidImage);
+    }
+    let bit_count = header.bit_count;
+    let supported = match header.compression {
+        BI_RGB => matches!(bit_count, 1 | 4 | 8 | 24 | 32),
+        BI_RLE8 => bit_count == 8 && !top_down,
+        BI_RLE4 => bit_count == 4 && !top_down,
+        _ => false,
+    };
+    if !supported {
+        return Err(InvalidImage);
+    }
+
+    let num_colours = match (bit_count, header.colours_used) {
+        (24 | 32, _) => 0,
+        (_, 0) => 1 << bit_count,
+        (_, colours_used) => (colours_used as usize).min(256),
+    };
+    //OS/2 palettes have three bytes per colour rather than four.
+    let entry_size = if header.size == 12 { 3 } else { 4 };
+    let palette_size = num_colours * entry_size;
+    let palette: Vec<[u8; 4]> = dib.get(header.size..header.size + palette_size).ok_or(InvalidImage)?
+        .chunks_exact(entry_size)
+        .map(|bgr| [bgr[2], bgr[1], bgr[0], 0xFF])
+        .collect();
+    let pixel_offset = pixel_offset.unwrap_or(header.size + palette_size);
+    let pixel_data = dib.get(pixel_offset..).ok_or(InvalidImage)?;
+
+    let mut image = Image { width, height, pixels: vec![0; num_pixels * 4] };
+    match header.compression {
+        BI_RLE8 | BI_RLE4 => decode_rle(&mut image, pixel_data, &palette, bit_count)?,
+        _ => {
+            //Rows are padded to a multiple of four bytes.
+            let stride = (width as usize * bit_count as usize).div_ceil(32) * 4;
+            for y in 0..height {
+                let stored_row = if top_down { y } else { height - 1 - y } as usize;
+                let row = pixel_data.get(stored_row * stride..(stored_row + 1) * stride).ok_or(InvalidImage)?;
+                for x in 0..width as usize {
+                    let colour = match bit_count {
+                        24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 0xFF],
+                        //Only icons give meaning to the fourth byte.
+                        32 => [row[x * 4 + 2], row[x * 4 + 1], row[x * 4], if icon { row[x * 4 + 3] } else { 0xFF }],
+                        _ => {
+                            let bit = x * bit_count as usize;
+                            let index = (row[bit / 8] >> (8 - bit_count as usize - bit % 8)) & ((1u16 << bit_count) - 1) as u8;
+                            *palette.get(index as usize).ok_or(InvalidImage)?
+                        },
+                    };
+                    image.set_pixel(x as u32, y, colour);
+                }
+            }
+            //Images without an alpha channel take their transparency from the mask, where a set bit is transparent.
+            if icon && bit_count != 32 {
+                let mask_offset = stride * height as usize;
+                let mask_stride = (width as usize).div_ceil(32) * 4;
+                for y in 0..height {
+                    let stored_row = if top_down { y } else { height - 1 - y } as usize;
+                    let start = mask_offset + stored_row * mask_stride;
+                    let row = pixel_data.get(start..start + mask_stride).ok_or(InvalidImage)?;
+                    for x in 0..width as usize {
+                        if row[x / 8] & (0x80 >> (x % 8)) != 0 {
+                            let index = (y as usize * width as usize + x) * 4;
+                            image.pixels[index + 3] = 0;
+                        }
+                    }
+                }
+            }
+        },
+    }
+    Ok(image)
+}
+
+/// Decodes RLE4 or RLE8 pixel data, which is always stored bottom up. Runs that overflow a row are clipped,
+/// and data that ends without an end-of-bitmap marker is treated as if it had one.
+fn decode_rle(image: &mut Image, data: &[u8], palette: &[[u8; 4]], bit_count: u16) -> Result<(), InvalidImage> {
+    let (mut x, mut y) = (0u32, 0u32);
+    let mut bytes = data.iter().copied();
+    let put = |image: &mut Image, x: &mut u32, y: u32, index: u8| -> Result<(), InvalidImage> {
+        let colour = *palette.get(index as usize).ok_or(InvalidImage)?;
+        if *x < image.width && y < image.height {
+            image.set_pixel(*x, image.height - 1 - y, colour);
+        }
+        *x += 1;
+        Ok(())
+    };
+    while let (Some(count), Some(value)) = (bytes.next(), bytes.next()) {
+        match (count, value) {
+            //An encoded run: `count` pixels, alternating between the two nibbles of `value` in RLE4.
+            (1.., _) if bit_count == 8 => (0..count).try_for_each(|_| put(image, &mut x, y, value))?,
+            (1.., _) => (0..count).try_for_each(|i| put(image, &mut x, y, if i % 2 == 0 { value >> 4 } else { value & 0x0F }))?,
+            (0, 0) => (x, y) = (0, y + 1),
+            (0, 1) => break,
+            (0, 2) => {
+                let (Some(dx), Some(dy)) = (bytes.next(), bytes.next()) else { break };
+                (x, y) = (x + dx as u32, y + dy as u32);
+            },
+            //An absolute run of `value` pixels, padded to a whole number of 16-bit words.
+            (0, _) => {
+                let num_bytes = if bit_count == 8 { value as usize } else { (value as usize).div_ceil(2) };
+                let run: Vec<u8> = bytes.by_ref().take(num_bytes.next_multiple_of(2)).collect();
+                for i in 0..value as usize {
+                    let index = match bit_count {
+                        8 => *run.get(i).ok_or(InvalidImage)?,
+                        _ => run.get(i / 2).map(|byte| if i % 2 == 0 { byte >> 4 } else { byte & 0x0F }).ok_or(InvalidImage)?,
+                    };
+                    put(image, &mut x, y, index)?;
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, InvalidImage> {
+    let bytes = data.get(offset..offset + 2).ok_or(InvalidImage)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, InvalidImage> {
+    let bytes = data.get(offset..offset + 4).ok_or(InvalidImage)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
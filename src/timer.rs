@@ -0,0 +1,69 @@
+//! The game clock, which runs from the first reveal until the game is won or lost.
+
+use std::time::{Duration, Instant};
+
+use crate::grid::GameState;
+
+/// The largest time the clock shows, and so the slowest time a high score can hold.
+pub const MAX_SECONDS: u32 = 999;
+
+/// Follows a board's [`GameState`] and measures how long the game has been played.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GameTimer {
+    started: Option<Instant>,
+    stopped: Option<Instant>,
+}
+
+impl GameTimer {
+    pub fn new() -> GameTimer {
+        GameTimer::default()
+    }
+
+    /// Resets, starts or stops the clock to match `game_state`. Call it after anything that can change the board.
+    pub fn update(&mut self, game_state: GameState) {
+        self.update_at(game_state, Instant::now());
+    }
+
+    /// As [`GameTimer::update`], treating `now` as the current time.
+    pub fn update_at(&mut self, game_state: GameState, now: Instant) {
+        match game_state {
+            GameState::NotStarted => *self = GameTimer::new(),
+            GameState::Playing => {
+                self.started.get_or_insert(now);
+            },
+            //The first reveal can also end the game, so the clock may start and stop at once.
+            GameState::Won | GameState::Lost { .. } => {
+                self.started.get_or_insert(now);
+                self.stopped.get_or_insert(now);
+            },
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some() && self.stopped.is_none()
+    }
+
+    /// The time shown on the clock in seconds. Like the original game it counts the second in progress,
+    /// so it reads 1 as soon as the game starts, and it stops at [`MAX_SECONDS`].
+    pub fn seconds(&self) -> u32 {
+        self.seconds_at(Instant::now())
+    }
+
+    /// As [`GameTimer::seconds`], treating `now` as the current time.
+    pub fn seconds_at(&self, now: Instant) -> u32 {
+        match self.started {
+            Some(started) => {
+                let elapsed = self.stopped.unwrap_or(now).saturating_duration_since(started).as_secs();
+                (elapsed + 1).min(MAX_SECONDS as u64) as u32
+            },
+            None => 0,
+        }
+    }
+
+    /// When the time shown will next change, or `None` if the clock is not running or has reached [`MAX_SECONDS`].
+    pub fn next_tick(&self) -> Option<Instant> {
+        let started = self.started.filter(|_| self.is_running())?;
+        let elapsed = Instant::now().saturating_duration_since(started).as_secs();
+        (elapsed + 1 < MAX_SECONDS as u64).then(|| started + Duration::from_secs(elapsed + 1))
+    }
+}
@@ -0,0 +1,318 @@
+//! Small modal windows drawn over the game, such as Best Times and Custom Field.
+
+use crate::{board_code::BoardCode, config::{BoardLimits, Config, Difficulty, HighScore, InvalidDifficulty}};
+
+use super::{font::{self, GLYPH_ADVANCE, GLYPH_HEIGHT}, panel, Rect, Shape, Theme};
+
+const TITLE_HEIGHT: u32 = 13;
+/// The space in pixels between the edges of a dialog and its contents, and between its lines and its buttons.
+const PADDING: u32 = 8;
+const LINE_HEIGHT: u32 = 11;
+const BUTTON_HEIGHT: u32 = 15;
+const BUTTON_GAP: u32 = 6;
+const MIN_BUTTON_WIDTH: u32 = 40;
+const FIELD_HEIGHT: u32 = 13;
+/// The vertical space in pixels taken by each text field, including the gap below it.
+const FIELD_ROW_HEIGHT: u32 = FIELD_HEIGHT + 3;
+/// Wide enough for five digits and the caret. Fields that take longer numbers are widened to fit them.
+const MIN_FIELD_WIDTH: u32 = 36;
+/// Wide enough for sixteen characters and the caret, so that a dialog with a board code fits a beginner board.
+/// Longer values scroll, showing their end.
+const MAX_FIELD_WIDTH: u32 = 3 + 16 * GLYPH_ADVANCE + 3;
+/// The space in pixels between a field's label and the field itself.
+const LABEL_GAP: u32 = 6;
+
+/// What pressing a dialog button asks the game to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogAction {
+    Close,
+    /// Forgets the best times, then shows them again.
+    ResetScores,
+    /// Starts a game with the board described by the fields of a Custom Field dialog, once
+    /// [`Dialog::custom_difficulty`] accepts them.
+    CustomField,
+    /// Replays the board whose code was typed into a Board Code dialog, once [`Dialog::entered_board_code`] accepts it.
+    PlayBoardCode,
+    /// Puts the code of the board being played on the clipboard.
+    CopyBoardCode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogButton {
+    pub label: String,
+    pub action: DialogAction,
+}
+impl DialogButton {
+    pub fn new(label: &str, action: DialogAction) -> DialogButton {
+        DialogButton { label: String::from(label), action }
+    }
+}
+
+/// The characters a [`TextField`] takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    /// Letters, digits and dashes, as in a [`BoardCode`].
+    BoardCode,
+}
+impl FieldKind {
+    pub fn accepts(self, character: char) -> bool {
+        match self {
+            FieldKind::Number    => character.is_ascii_digit(),
+            FieldKind::BoardCode => character.is_ascii_alphanumeric() || character == '-',
+        }
+    }
+}
+
+/// A labelled box that takes text typed by the player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextField {
+    pub label: String,
+    /// Only ever holds up to `max_length` characters, all of which its `kind` accepts.
+    pub value: String,
+    pub kind: FieldKind,
+    /// The length of the longest value the field takes, beyond which typing is ignored.
+    pub max_length: usize,
+}
+impl TextField {
+    /// A field holding `value` that takes numbers up to `max`.
+    pub fn new(label: &str, value: u32, max: u32) -> TextField {
+        TextField { label: String::from(label), value: value.to_string(), kind: FieldKind::Number, max_length: max.to_string().len() }
+    }
+
+    /// An empty field for a board code within `limits`.
+    pub fn board_code(label: &str, limits: &BoardLimits) -> TextField {
+        TextField { label: String::from(label), value: String::new(), kind: FieldKind::BoardCode, max_length: BoardCode::max_length(limits) }
+    }
+}
+
+/// A titled box of text and text fields with a row of buttons, centred in the window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialog {
+    pub title: String,
+    pub lines: Vec<String>,
+    /// Laid out one above the other, below the lines.
+    pub fields: Vec<TextField>,
+    /// The field that typing goes into.
+    pub focused: usize,
+    /// Why the values in the fields were refused, shown below them.
+    pub error: Option<String>,
+    /// Laid out from left to right.
+    pub buttons: Vec<DialogButton>,
+    /// The button chosen by Return, which is drawn with a heavier border.
+    pub default_button: usize,
+    /// The button the mouse went down on.
+    pressed: Option<usize>,
+}
+
+impl Dialog {
+    pub fn new(title: &str, lines: Vec<String>, buttons: Vec<DialogButton>, default_button: usize) -> Dialog {
+        Dialog { title: String::from(title), lines, fields: Vec::new(), focused: 0, error: None, buttons, default_button, pressed: None }
+    }
+
+    /// The fastest time at each difficulty, like Best Times in the original game.
+    pub fn best_times(config: &Config) -> Dialog {
+        let mut lines = vec![String::from("Fastest Mine Sweepers")];
+        for (difficulty, HighScore { name, time }) in [("Beginner", &config.beginner_score), ("Intermediate", &config.intermediate_score), ("Expert", &config.expert_score)] {
+            lines.push(format!("{difficulty}: {time} seconds"));
+            lines.push(format!("  {name}"));
+        }
+        Dialog::new("Best Times", lines, vec![DialogButton::new("Reset Scores", DialogAction::ResetScores), DialogButton::new("OK", DialogAction::Close)], 1)
+    }
+
+    pub fn about() -> Dialog {
+        let lines = vec![
+            format!("Minesweeper {}", env!("CARGO_PKG_VERSION")),
+            String::new(),
+            String::from("A Rust remake of the"),
+            String::from("classic Windows game."),
+        ];
+        Dialog::new("About Minesweeper", lines, vec![DialogButton::new("OK", DialogAction::Close)], 0)
+    }
+
+    /// Asks for the height, width and number of mines of a custom board, starting from the current board.
+    /// Each field is long enough for the largest value the board limits allow in it.
+    pub fn custom_field(config: &Config) -> Dialog {
+        let (width, height) = config.difficulty.dimensions();
+        let limits = config.board_limits;
+        let fields = vec![
+            TextField::new("Height:", height, limits.max_height),
+            TextField::new("Width:", width, limits.max_width),
+            TextField::new("Mines:", config.difficulty.num_mines(), limits.max_mines()),
+        ];
+        Dialog { fields, ..Dialog::new("Custom Field", Vec::new(), vec![DialogButton::new("OK", DialogAction::CustomField), DialogButton::new("Cancel", DialogAction::Close)], 0) }
+    }
+
+    /// The board described by the fields of a [`Dialog::custom_field`] dialog. If a value is outside `limits` it
+    /// is left for the player to correct, with the reason shown in the dialog and that field focused.
+    pub fn custom_difficulty(&mut self, limits: &BoardLimits) -> Option<Difficulty> {
+        //An empty field counts as zero, which is always refused.
+        let [height, width, num_mines] = [0, 1, 2].map(|field| self.fields[field].value.parse().unwrap_or(0));
+        match Difficulty::try_with_limits(width, height, num_mines, limits) {
+            Ok(difficulty) => Some(difficulty),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.focused = match e {
+                    InvalidDifficulty::Height { .. } => 0,
+                    InvalidDifficulty::Width { .. }  => 1,
+                    InvalidDifficulty::Mines { .. }  => 2,
+                };
+                None
+            },
+        }
+    }
+
+    /// Shows the code of the board being played, if it has one, to be copied, and takes a code to play instead.
+    pub fn board_code(current: Option<BoardCode>, limits: &BoardLimits) -> Dialog {
+        let (lines, copy) = match current {
+            Some(board_code) => (vec![String::from("This board:"), board_code.to_string()], vec![DialogButton::new("Copy", DialogAction::CopyBoardCode)]),
+            None => (vec![String::from("The legacy generator"), String::from("gives boards no code.")], Vec::new()),
+        };
+        let buttons = [vec![DialogButton::new("Play", DialogAction::PlayBoardCode)], copy, vec![DialogButton::new("Cancel", DialogAction::Close)]].concat();
+        Dialog { fields: vec![TextField::board_code("Code:", limits)], ..Dialog::new("Board Code", lines, buttons, 0) }
+    }
+
+    /// The board whose code was typed into a [`Dialog::board_code`] dialog. A code that cannot be read, or is for
+    /// a custom board outside `limits`, is left for the player to correct, with the reason shown in the dialog.
+    pub fn entered_board_code(&mut self, limits: &BoardLimits) -> Option<BoardCode> {
+        match BoardCode::parse_with_limits(&self.fields[0].value, limits) {
+            Ok(board_code) => Some(board_code),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                None
+            },
+        }
+    }
+
+    /// Types `character` into the focused field. Characters the field takes are added, backspace takes the last
+    /// one away, and tab moves on to the next field.
+    pub fn type_character(&mut self, character: char) {
+        let Some(field) = self.fields.get_mut(self.focused) else { return };
+        match character {
+            character if field.kind.accepts(character) && field.value.len() < field.max_length => field.value.push(character),
+            '\u{8}' => {
+                field.value.pop();
+            },
+            '\t' => self.focused = (self.focused + 1) % self.fields.len(),
+            _ => {},
+        }
+    }
+
+    /// The rectangle the dialog takes up, centred in a window of `window` pixels.
+    pub fn rect(&self, window: (u32, u32)) -> Rect {
+        let buttons_width = self.buttons.iter().map(|button| button_width(button) + BUTTON_GAP).sum::<u32>().saturating_sub(BUTTON_GAP);
+        let fields_width = if self.fields.is_empty() { 0 } else { self.label_width() + LABEL_GAP + self.field_width() };
+        let content_width = self.lines.iter().chain(&self.error).map(|line| font::text_width(line)).chain([font::text_width(&self.title), fields_width, buttons_width]).max().unwrap_or(0);
+        let width = PADDING + content_width + PADDING;
+        let error_height = if self.error.is_some() { LINE_HEIGHT } else { 0 };
+        let height = TITLE_HEIGHT + PADDING + self.lines.len() as u32 * LINE_HEIGHT + self.fields.len() as u32 * FIELD_ROW_HEIGHT + error_height + PADDING + BUTTON_HEIGHT + PADDING;
+        Rect::new((window.0 as i32 - width as i32) / 2, (window.1 as i32 - height as i32) / 2, width, height)
+    }
+
+    /// The width in pixels of the widest field label.
+    fn label_width(&self) -> u32 {
+        self.fields.iter().map(|field| font::text_width(&field.label)).max().unwrap_or(0)
+    }
+
+    /// The width in pixels of every text field, enough for the longest value any of them takes up to [`MAX_FIELD_WIDTH`].
+    fn field_width(&self) -> u32 {
+        let max_length = self.fields.iter().map(|field| field.max_length as u32).max().unwrap_or(0);
+        (3 + max_length * GLYPH_ADVANCE + 3).clamp(MIN_FIELD_WIDTH, MAX_FIELD_WIDTH)
+    }
+
+    /// The rectangle of a text field, beside its label and below the lines of the dialog.
+    pub fn field_rect(&self, window: (u32, u32), field: usize) -> Rect {
+        let rect = self.rect(window);
+        let x = rect.x + (PADDING + self.label_width() + LABEL_GAP) as i32;
+        let y = rect.y + (TITLE_HEIGHT + PADDING + self.lines.len() as u32 * LINE_HEIGHT + field as u32 * FIELD_ROW_HEIGHT) as i32;
+        Rect::new(x, y, self.field_width(), FIELD_HEIGHT)
+    }
+
+    /// The rectangle of a button, centred as a row along the bottom of the dialog.
+    pub fn button_rect(&self, window: (u32, u32), button: usize) -> Rect {
+        let rect = self.rect(window);
+        let buttons_width = self.buttons.iter().map(|button| button_width(button) + BUTTON_GAP).sum::<u32>() - BUTTON_GAP;
+        let x = rect.x + (rect.width - buttons_width) as i32 / 2 + self.buttons[..button].iter().map(|button| (button_width(button) + BUTTON_GAP) as i32).sum::<i32>();
+        let y = rect.y + (rect.height - PADDING - BUTTON_HEIGHT) as i32;
+        Rect::new(x, y, button_width(&self.buttons[button]), BUTTON_HEIGHT)
+    }
+
+    fn button_at(&self, window: (u32, u32), position: Option<(i32, i32)>) -> Option<usize> {
+        let position = position?;
+        (0..self.buttons.len()).find(|&button| self.button_rect(window, button).contains(position))
+    }
+
+    /// Handles a mouse button going down, holding down the button under `position` if there is one,
+    /// or focusing the field under it.
+    pub fn press(&mut self, window: (u32, u32), position: Option<(i32, i32)>) {
+        self.pressed = self.button_at(window, position);
+        if let Some(field) = position.and_then(|position| (0..self.fields.len()).find(|&field| self.field_rect(window, field).contains(position))) {
+            self.focused = field;
+        }
+    }
+
+    /// Handles a mouse button coming back up, returning the action of the button it went down on if it is still over it.
+    pub fn release(&mut self, window: (u32, u32), position: Option<(i32, i32)>) -> Option<DialogAction> {
+        let pressed = self.pressed.take()?;
+        (self.button_at(window, position) == Some(pressed)).then(|| self.buttons[pressed].action)
+    }
+
+    pub fn default_action(&self) -> DialogAction {
+        self.buttons[self.default_button].action
+    }
+
+    pub fn shapes(&self, window: (u32, u32), theme: Theme) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        let rect = self.rect(window);
+        panel(&mut shapes, rect, theme);
+        let text_inset = ((TITLE_HEIGHT - GLYPH_HEIGHT) / 2) as i32;
+        shapes.push(Shape::Rect { rect: Rect::new(rect.x + 1, rect.y + 1, rect.width - 2, TITLE_HEIGHT - 1), colour: theme.highlight });
+        shapes.push(Shape::Text { position: (rect.x + PADDING as i32, rect.y + text_inset), text: self.title.clone(), colour: theme.highlight_text });
+        for (index, line) in self.lines.iter().enumerate() {
+            let y = rect.y + (TITLE_HEIGHT + PADDING + index as u32 * LINE_HEIGHT) as i32;
+            shapes.push(Shape::Text { position: (rect.x + PADDING as i32, y), text: line.clone(), colour: theme.text });
+        }
+
+        let text_inset = ((FIELD_HEIGHT - GLYPH_HEIGHT) / 2) as i32;
+        for (index, field) in self.fields.iter().enumerate() {
+            let Rect { x, y, width, height } = self.field_rect(window, index);
+            shapes.push(Shape::Text { position: (rect.x + PADDING as i32, y + text_inset), text: field.label.clone(), colour: theme.text });
+            //Fields are sunk into the dialog, the reverse of a raised panel.
+            shapes.push(Shape::Rect { rect: Rect::new(x, y, width, height), colour: theme.light });
+            shapes.push(Shape::Rect { rect: Rect::new(x, y, width - 1, height - 1), colour: theme.shadow });
+            shapes.push(Shape::Rect { rect: Rect::new(x + 1, y + 1, width - 2, height - 2), colour: theme.light });
+            //Only the end of a value too long for the field is shown, leaving room for the caret.
+            let visible = ((width - 3 - 3) / GLYPH_ADVANCE) as usize;
+            let text: String = field.value.chars().skip(field.value.chars().count().saturating_sub(visible)).collect();
+            let caret_x = x + 3 + font::text_width(&text) as i32 + 1;
+            shapes.push(Shape::Text { position: (x + 3, y + text_inset), text, colour: theme.text });
+            if index == self.focused {
+                shapes.push(Shape::Rect { rect: Rect::new(caret_x, y + 2, 1, height - 4), colour: theme.text });
+            }
+        }
+        if let Some(error) = &self.error {
+            let y = rect.y + (TITLE_HEIGHT + PADDING + self.lines.len() as u32 * LINE_HEIGHT + self.fields.len() as u32 * FIELD_ROW_HEIGHT) as i32;
+            shapes.push(Shape::Text { position: (rect.x + PADDING as i32, y), text: error.clone(), colour: theme.error });
+        }
+
+        let text_inset = ((BUTTON_HEIGHT - GLYPH_HEIGHT) / 2) as i32;
+        for (index, button) in self.buttons.iter().enumerate() {
+            let button_rect = self.button_rect(window, index);
+            let mut border = button_rect;
+            if index == self.default_button {
+                shapes.push(Shape::Rect { rect: button_rect, colour: theme.text });
+                border = Rect::new(button_rect.x + 1, button_rect.y + 1, button_rect.width - 2, button_rect.height - 2);
+            }
+            //A held button looks pushed in, with its label nudged down and to the right.
+            let pushed = self.pressed == Some(index);
+            panel(&mut shapes, border, if pushed { Theme { light: theme.shadow, shadow: theme.light, ..theme } } else { theme });
+            let x = button_rect.x + (button_rect.width - font::text_width(&button.label)) as i32 / 2 + pushed as i32;
+            shapes.push(Shape::Text { position: (x, button_rect.y + text_inset + pushed as i32), text: button.label.clone(), colour: theme.text });
+        }
+        shapes
+    }
+}
+
+fn button_width(button: &DialogButton) -> u32 {
+    (PADDING + font::text_width(&button.label) + PADDING).max(MIN_BUTTON_WIDTH)
+}
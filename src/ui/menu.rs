@@ -0,0 +1,367 @@
+//! The Game, Options and Help menus along the top of the window.
+
+use crate::{board_code::BoardCode, config::{Config, Difficulty, DifficultyType, GraphicsType, KeyAction, SafeFirstClick, Scale}};
+
+use super::{font::{self, CHECK_MARK, GLYPH_HEIGHT}, panel, Rect, Shape, Theme};
+
+/// The height in pixels of the menu bar, which sits above everything else in the window.
+pub const MENU_BAR_HEIGHT: u32 = 14;
+/// The space in pixels either side of a menu title.
+const TITLE_PADDING: u32 = 6;
+const ITEM_HEIGHT: u32 = 13;
+const SEPARATOR_HEIGHT: u32 = 7;
+/// The space in pixels to the left of an item's label, where its check mark goes.
+const CHECK_COLUMN: u32 = 12;
+/// The least space in pixels between an item's label and its shortcut.
+const SHORTCUT_GAP: u32 = 12;
+const RIGHT_PADDING: u32 = 8;
+
+/// What choosing a menu item asks the game to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuCommand {
+    NewGame,
+    /// Starts a new game at this difficulty, one of the presets or a custom board from the Custom Field dialog.
+    Difficulty(Difficulty),
+    CustomField,
+    /// Opens the Board Code dialog, to copy the code of the board being played or type in another.
+    BoardCode,
+    /// Starts a new game on the board with this code, from the Board Code dialog.
+    Replay(BoardCode),
+    ToggleMarks,
+    ToggleColour,
+    ToggleSound,
+    BestTimes,
+    Exit,
+    Scale(Scale),
+    SafeFirstClick(SafeFirstClick),
+    ToggleLegacyRng,
+    GraphicsType(GraphicsType),
+    About,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuEntry {
+    Item {
+        label: String,
+        /// The first key bound to the same action, if there is one.
+        shortcut: Option<String>,
+        checked: bool,
+        /// Disabled items are greyed out and cannot be chosen.
+        enabled: bool,
+        command: MenuCommand,
+    },
+    Separator,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu {
+    pub title: &'static str,
+    pub entries: Vec<MenuEntry>,
+}
+
+/// The menus for `config`, with the settings it has turned on checked and its key bindings shown as shortcuts.
+pub fn menus(config: &Config) -> Vec<Menu> {
+    let item = |label: &str, checked: bool, command: MenuCommand| {
+        let shortcut = shortcut_action(command).and_then(|action| config.key_bindings.keys(action).first()).map(|key| key_label(key));
+        MenuEntry::Item { label: String::from(label), shortcut, checked, enabled: true, command }
+    };
+    let difficulty_type = config.difficulty.difficulty_type();
+    let difficulty = |label: &str, difficulty: Difficulty| item(label, difficulty_type == difficulty.difficulty_type(), MenuCommand::Difficulty(difficulty));
+    let scale = |label: &str, scale: Scale| item(label, config.scale == scale, MenuCommand::Scale(scale));
+    let safe_first_click = |label: &str, safe_first_click: SafeFirstClick| item(label, config.safe_first_click == safe_first_click, MenuCommand::SafeFirstClick(safe_first_click));
+    let graphics_type = |label: &str, graphics_type: GraphicsType| item(label, config.graphics_type == graphics_type, MenuCommand::GraphicsType(graphics_type));
+    vec![
+        Menu { title: "Game", entries: vec![
+            item("New", false, MenuCommand::NewGame),
+            item("Board Code...", false, MenuCommand::BoardCode),
+            MenuEntry::Separator,
+            difficulty("Beginner", Difficulty::BEGINNER),
+            difficulty("Intermediate", Difficulty::INTERMEDIATE),
+            difficulty("Expert", Difficulty::EXPERT),
+            item("Custom...", difficulty_type == DifficultyType::Custom, MenuCommand::CustomField),
+            MenuEntry::Separator,
+            item("Marks (?)", config.marks_enabled, MenuCommand::ToggleMarks),
+            item("Color", config.colour_enabled, MenuCommand::ToggleColour),
+            item("Sound", config.sound_enabled, MenuCommand::ToggleSound),
+            MenuEntry::Separator,
+            item("Best Times...", false, MenuCommand::BestTimes),
+            MenuEntry::Separator,
+            item("Exit", false, MenuCommand::Exit),
+        ]},
+        Menu { title: "Options", entries: vec![
+            scale("Auto Zoom", Scale::Auto),
+            scale("1x", Scale::Fixed(1)),
+            scale("2x", Scale::Fixed(2)),
+            scale("3x", Scale::Fixed(3)),
+            scale("4x", Scale::Fixed(4)),
+            MenuEntry::Separator,
+            safe_first_click("No Safe First Click", SafeFirstClick::Off),
+            safe_first_click("Safe First Square", SafeFirstClick::Square),
+            safe_first_click("Safe Neighbourhood", SafeFirstClick::Neighbourhood),
+            MenuEntry::Separator,
+            item("Legacy Generator", config.legacy_rng, MenuCommand::ToggleLegacyRng),
+            MenuEntry::Separator,
+            graphics_type("OpenGL Renderer", GraphicsType::OpenGL),
+            graphics_type("Software Renderer", GraphicsType::Software),
+        ]},
+        Menu { title: "Help", entries: vec![
+            item("About Minesweeper...", false, MenuCommand::About),
+        ]},
+    ]
+}
+
+/// The key action that does the same as `command`, whose keys are shown beside it.
+fn shortcut_action(command: MenuCommand) -> Option<KeyAction> {
+    match command {
+        MenuCommand::NewGame => Some(KeyAction::NewGame),
+        MenuCommand::Difficulty(difficulty) => match difficulty.difficulty_type() {
+            DifficultyType::Beginner     => Some(KeyAction::Beginner),
+            DifficultyType::Intermediate => Some(KeyAction::Intermediate),
+            DifficultyType::Expert       => Some(KeyAction::Expert),
+            DifficultyType::Custom       => None,
+        },
+        MenuCommand::ToggleColour => Some(KeyAction::Colour),
+        _ => None,
+    }
+}
+
+/// How a key is shown as a shortcut: winit names the number keys `Key1` to `Key0`, but they are labelled with the digit alone.
+fn key_label(key: &str) -> String {
+    match key.strip_prefix("Key") {
+        Some(digit) if digit.len() == 1 => String::from(digit),
+        _ => String::from(key),
+    }
+}
+
+/// Where the pointer is over the menu bar or the open menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuHit {
+    /// The title of the menu with this index.
+    Title(usize),
+    /// The entry with this index in the open menu.
+    Entry(usize),
+}
+
+/// A key pressed while a menu is open. As in Windows, the arrow keys move between items and menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Chooses the highlighted item.
+    Choose,
+    Close,
+}
+
+/// The menus and which one, if any, is open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuBar {
+    menus: Vec<Menu>,
+    open: Option<usize>,
+    /// The entry of the open menu under the pointer.
+    highlighted: Option<usize>,
+}
+
+impl MenuBar {
+    pub fn new(config: &Config) -> MenuBar {
+        MenuBar { menus: menus(config), open: None, highlighted: None }
+    }
+
+    /// Rebuilds the menus from `config`, so that they follow settings changed by other means, such as the keyboard.
+    pub fn sync(&mut self, config: &Config) {
+        self.menus = menus(config);
+    }
+
+    pub fn menus(&self) -> &[Menu] {
+        &self.menus
+    }
+
+    /// The index of the open menu.
+    pub fn open_menu(&self) -> Option<usize> {
+        self.open
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    pub fn close(&mut self) {
+        self.open = None;
+        self.highlighted = None;
+    }
+
+    pub fn title_rect(&self, menu: usize) -> Rect {
+        let x: u32 = self.menus[..menu].iter().map(|menu| title_width(menu.title)).sum();
+        Rect::new(x as i32, 0, title_width(self.menus[menu].title), MENU_BAR_HEIGHT)
+    }
+
+    /// The rectangle a menu takes up when open, hanging below its title. Near the right edge of a window `width`
+    /// pixels wide, it is moved left so that none of it is cut off.
+    pub fn dropdown_rect(&self, width: u32, menu: usize) -> Rect {
+        let entries = &self.menus[menu].entries;
+        let label_width = entries.iter().filter_map(|entry| match entry {
+            MenuEntry::Item { label, shortcut, .. } => Some(font::text_width(label) + shortcut.as_ref().map_or(0, |shortcut| SHORTCUT_GAP + font::text_width(shortcut))),
+            MenuEntry::Separator => None,
+        }).max().unwrap_or(0);
+        let height: u32 = entries.iter().map(entry_height).sum();
+        let dropdown_width = 1 + CHECK_COLUMN + label_width + RIGHT_PADDING + 1;
+        let x = self.title_rect(menu).x.min(width as i32 - dropdown_width as i32).max(0);
+        Rect::new(x, MENU_BAR_HEIGHT as i32, dropdown_width, 1 + height + 1)
+    }
+
+    /// The rectangle of an entry of an open menu, inside its border.
+    pub fn entry_rect(&self, width: u32, menu: usize, entry: usize) -> Rect {
+        let dropdown = self.dropdown_rect(width, menu);
+        let y: u32 = self.menus[menu].entries[..entry].iter().map(entry_height).sum();
+        Rect::new(dropdown.x + 1, dropdown.y + 1 + y as i32, dropdown.width - 2, entry_height(&self.menus[menu].entries[entry]))
+    }
+
+    /// What is under `position`: a title, or an entry of the open menu.
+    pub fn hit(&self, width: u32, position: (i32, i32)) -> Option<MenuHit> {
+        if let Some(menu) = self.open {
+            if let Some(entry) = (0..self.menus[menu].entries.len()).find(|&entry| self.entry_rect(width, menu, entry).contains(position)) {
+                return Some(MenuHit::Entry(entry));
+            }
+        }
+        (0..self.menus.len()).find(|&menu| self.title_rect(menu).contains(position)).map(MenuHit::Title)
+    }
+
+    /// Handles a mouse button going down. A press on a title opens or closes its menu, and a press anywhere
+    /// else closes the open menu, as in Windows. Returns `true` if the press was for the menus, in which case
+    /// the game should ignore it.
+    pub fn press(&mut self, width: u32, position: Option<(i32, i32)>) -> bool {
+        let hit = position.and_then(|position| self.hit(width, position));
+        let over_open_menu = self.open.is_some_and(|menu| position.is_some_and(|position| self.dropdown_rect(width, menu).contains(position)));
+        match hit {
+            Some(MenuHit::Title(menu)) if self.open == Some(menu) => self.close(),
+            Some(MenuHit::Title(menu)) => {
+                self.open = Some(menu);
+                self.highlighted = None;
+            },
+            _ if over_open_menu => {},
+            _ if self.is_open() => self.close(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Handles a mouse button coming back up. Releasing over an enabled item closes the menu and returns its command,
+    /// so an item can be chosen either by clicking it or by dragging down to it from the title.
+    pub fn release(&mut self, width: u32, position: Option<(i32, i32)>) -> Option<MenuCommand> {
+        let menu = self.open?;
+        let Some(MenuHit::Entry(entry)) = position.and_then(|position| self.hit(width, position)) else { return None };
+        match self.menus[menu].entries[entry] {
+            MenuEntry::Item { enabled: true, command, .. } => {
+                self.close();
+                Some(command)
+            },
+            _ => None,
+        }
+    }
+
+    /// Follows the pointer while a menu is open, highlighting the entry under it and moving to another menu when
+    /// it crosses that menu's title. Returns `true` if anything changed.
+    pub fn hover(&mut self, width: u32, position: Option<(i32, i32)>) -> bool {
+        let Some(open) = self.open else { return false };
+        let (open, highlighted) = match position.and_then(|position| self.hit(width, position)) {
+            Some(MenuHit::Title(menu)) => (menu, None),
+            Some(MenuHit::Entry(entry)) => (open, Some(entry)),
+            None => (open, None),
+        };
+        let changed = (Some(open), highlighted) != (self.open, self.highlighted);
+        (self.open, self.highlighted) = (Some(open), highlighted);
+        changed
+    }
+
+    /// Handles a key pressed while a menu is open, returning the command of the item chosen with [`MenuKey::Choose`].
+    /// Up and down skip over separators and disabled items, wrapping around at either end.
+    pub fn key(&mut self, key: MenuKey) -> Option<MenuCommand> {
+        let menu = self.open?;
+        let entries = &self.menus[menu].entries;
+        let enabled: Vec<usize> = (0..entries.len()).filter(|&entry| matches!(entries[entry], MenuEntry::Item { enabled: true, .. })).collect();
+        let position = self.highlighted.and_then(|highlighted| enabled.iter().position(|&entry| entry == highlighted));
+        match key {
+            MenuKey::Up if !enabled.is_empty() => {
+                self.highlighted = Some(enabled[position.map_or(enabled.len() - 1, |position| (position + enabled.len() - 1) % enabled.len())]);
+            },
+            MenuKey::Down if !enabled.is_empty() => {
+                self.highlighted = Some(enabled[position.map_or(0, |position| (position + 1) % enabled.len())]);
+            },
+            MenuKey::Left => (self.open, self.highlighted) = (Some((menu + self.menus.len() - 1) % self.menus.len()), None),
+            MenuKey::Right => (self.open, self.highlighted) = (Some((menu + 1) % self.menus.len()), None),
+            MenuKey::Choose => {
+                if let Some(MenuEntry::Item { enabled: true, command, .. }) = self.highlighted.map(|highlighted| &self.menus[menu].entries[highlighted]) {
+                    let command = *command;
+                    self.close();
+                    return Some(command);
+                }
+            },
+            MenuKey::Close => self.close(),
+            _ => {},
+        }
+        None
+    }
+
+    /// The menu bar across a window `width` pixels wide, and the open menu if there is one.
+    pub fn shapes(&self, width: u32, theme: Theme) -> Vec<Shape> {
+        let mut shapes = vec![Shape::Rect { rect: Rect::new(0, 0, width, MENU_BAR_HEIGHT), colour: theme.face }];
+        let text_inset = ((MENU_BAR_HEIGHT - GLYPH_HEIGHT) / 2) as i32;
+        for (index, menu) in self.menus.iter().enumerate() {
+            let rect = self.title_rect(index);
+            let colour = if self.open == Some(index) {
+                shapes.push(Shape::Rect { rect, colour: theme.highlight });
+                theme.highlight_text
+            } else {
+                theme.text
+            };
+            shapes.push(Shape::Text { position: (rect.x + TITLE_PADDING as i32, rect.y + text_inset), text: String::from(menu.title), colour });
+        }
+
+        let Some(open) = self.open else { return shapes };
+        let dropdown = self.dropdown_rect(width, open);
+        panel(&mut shapes, dropdown, theme);
+        let text_inset = ((ITEM_HEIGHT - GLYPH_HEIGHT) / 2) as i32;
+        for (index, entry) in self.menus[open].entries.iter().enumerate() {
+            let rect = self.entry_rect(width, open, index);
+            match entry {
+                MenuEntry::Separator => {
+                    let y = rect.y + (SEPARATOR_HEIGHT / 2) as i32;
+                    shapes.push(Shape::Rect { rect: Rect::new(rect.x + 1, y - 1, rect.width - 2, 1), colour: theme.shadow });
+                    shapes.push(Shape::Rect { rect: Rect::new(rect.x + 1, y, rect.width - 2, 1), colour: theme.light });
+                },
+                MenuEntry::Item { label, shortcut, checked, enabled, .. } => {
+                    let colour = if !enabled {
+                        theme.disabled_text
+                    } else if self.highlighted == Some(index) {
+                        shapes.push(Shape::Rect { rect, colour: theme.highlight });
+                        theme.highlight_text
+                    } else {
+                        theme.text
+                    };
+                    let y = rect.y + text_inset;
+                    if *checked {
+                        shapes.push(Shape::Text { position: (rect.x + 3, y), text: String::from(CHECK_MARK), colour });
+                    }
+                    shapes.push(Shape::Text { position: (rect.x + CHECK_COLUMN as i32, y), text: label.clone(), colour });
+                    if let Some(shortcut) = shortcut {
+                        let x = rect.x + rect.width as i32 - RIGHT_PADDING as i32 - font::text_width(shortcut) as i32;
+                        shapes.push(Shape::Text { position: (x, y), text: shortcut.clone(), colour });
+                    }
+                },
+            }
+        }
+        shapes
+    }
+}
+
+fn title_width(title: &str) -> u32 {
+    TITLE_PADDING + font::text_width(title) + TITLE_PADDING
+}
+
+fn entry_height(entry: &MenuEntry) -> u32 {
+    match entry {
+        MenuEntry::Item { .. } => ITEM_HEIGHT,
+        MenuEntry::Separator => SEPARATOR_HEIGHT,
+    }
+}
@@ -0,0 +1,70 @@
+//! The menu bar and dialogs drawn inside the game window.
+//!
+//! Widgets work in the same unscaled pixels as the rest of the window and describe themselves as a list of
+//! [`Shape`]s, so any renderer that can fill a rectangle and draw text from [`font`] can show them.
+
+pub mod dialog;
+pub mod font;
+pub mod menu;
+
+pub use self::{dialog::{Dialog, DialogAction, FieldKind, TextField}, menu::{MenuBar, MenuCommand, MENU_BAR_HEIGHT}};
+
+/// A rectangle in pixels from the top left corner of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+impl Rect {
+    pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    pub fn contains(&self, position: (i32, i32)) -> bool {
+        (self.x..self.x + self.width as i32).contains(&position.0) && (self.y..self.y + self.height as i32).contains(&position.1)
+    }
+}
+
+/// Something for a renderer to draw, with colours given as `0xRRGGBB`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    Rect { rect: Rect, colour: u32 },
+    /// A single line of text with its top left corner at `position`.
+    Text { position: (i32, i32), text: String, colour: u32 },
+}
+
+/// The colours of the menus and dialogs, which follow the colour setting like the rest of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The background of the menu bar, menus and dialogs.
+    pub face: u32,
+    pub text: u32,
+    /// The background of the item under the pointer, and of dialog title bars.
+    pub highlight: u32,
+    pub highlight_text: u32,
+    pub disabled_text: u32,
+    /// Validation messages in dialogs.
+    pub error: u32,
+    /// The lit top and left edges of raised borders.
+    pub light: u32,
+    /// The bottom and right edges of raised borders.
+    pub shadow: u32,
+}
+impl Theme {
+    pub const COLOUR: Theme = Theme { face: 0xC0C0C0, text: 0x000000, highlight: 0x000080, highlight_text: 0xFFFFFF, disabled_text: 0x808080, error: 0xFF0000, light: 0xFFFFFF, shadow: 0x808080 };
+    pub const MONOCHROME: Theme = Theme { face: 0xFFFFFF, text: 0x000000, highlight: 0x000000, highlight_text: 0xFFFFFF, disabled_text: 0x808080, error: 0x000000, light: 0xFFFFFF, shadow: 0x000000 };
+
+    pub fn new(colour: bool) -> Theme {
+        if colour { Theme::COLOUR } else { Theme::MONOCHROME }
+    }
+}
+
+/// Fills `rect` with `face` inside a one pixel raised border.
+fn panel(shapes: &mut Vec<Shape>, rect: Rect, theme: Theme) {
+    let Rect { x, y, width, height } = rect;
+    shapes.push(Shape::Rect { rect, colour: theme.shadow });
+    shapes.push(Shape::Rect { rect: Rect::new(x, y, width.saturating_sub(1), height.saturating_sub(1)), colour: theme.light });
+    shapes.push(Shape::Rect { rect: Rect::new(x + 1, y + 1, width.saturating_sub(2), height.saturating_sub(2)), colour: theme.face });
+}
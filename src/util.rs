@@ -1,28 +1,91 @@
-use rand::{rngs::ThreadRng, Rng};
+use rand::{rngs::ThreadRng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
+/// Source of random numbers used to place mines on a [`GameBoard`](crate::grid::GameBoard).
 pub trait RandomNumberGenerator {
+    /// Returns a random value in the range `0..max`.
     fn random_u32(&mut self, max: u32) -> usize;
+
+    /// Chooses `num_mines` distinct squares of a `num_rows` by `num_columns` board, returned as indices into
+    /// [`GameBoard::grid`](crate::grid::GameBoard::grid) in the order they were drawn.
+    ///
+    /// Every square is equally likely to be chosen, and the number of draws does not depend on how dense the board is.
+    fn choose_mine_squares(&mut self, num_rows: usize, num_columns: usize, num_mines: usize) -> Vec<usize> {
+        partial_shuffle(self, num_rows * num_columns, num_mines)
+    }
+}
+
+/// Draws `count` distinct values from `0..len` with a partial Fisher-Yates shuffle.
+pub fn partial_shuffle<R: RandomNumberGenerator + ?Sized>(random_number_generator: &mut R, len: usize, count: usize) -> Vec<usize> {
+    let count = count.min(len);
+    let mut squares: Vec<usize> = (0..len).collect();
+    for i in 0..count {
+        let j = i + random_number_generator.random_u32((len - i) as u32);
+        squares.swap(i, j);
+    }
+    squares.truncate(count);
+    squares
 }
 
-pub struct LegacyRandomNumberGenerator;
+/// A pure Rust copy of the Microsoft C runtime's `rand()`, which `winmine.exe` used to place its mines.
+///
+/// The original seeded it with `srand()` from the low word of `GetTickCount()` and reduced each value
+/// with `rand() % max`, so a given seed produces the same board here as in the original game on every platform.
+pub struct LegacyRandomNumberGenerator {
+    state: u32,
+}
 impl LegacyRandomNumberGenerator {
     #[cfg(target_os="windows")]
     pub fn new() -> LegacyRandomNumberGenerator {
-        unsafe { srand(lo_word(GetTickCount()) as u32) }
-        LegacyRandomNumberGenerator {}
+        LegacyRandomNumberGenerator::with_seed(lo_word(unsafe { GetTickCount() }) as u32)
     }
-    #[cfg(target_os="linux")]
+    #[cfg(not(target_os="windows"))]
     pub fn new() -> LegacyRandomNumberGenerator {
-        unsafe { srand(lo_word(get_tick_count()) as u32) }
-        LegacyRandomNumberGenerator {}
+        LegacyRandomNumberGenerator::with_seed(lo_word(get_tick_count()) as u32)
+    }
+    /// Equivalent to calling `srand(seed)`.
+    pub fn with_seed(seed: u32) -> LegacyRandomNumberGenerator {
+        LegacyRandomNumberGenerator { state: seed }
+    }
+    /// Equivalent to calling `rand()`: returns the next value in the range `0..=0x7fff`.
+    pub fn rand(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(214013).wrapping_add(2531011);
+        (self.state >> 16) & 0x7fff
+    }
+}
+impl Default for LegacyRandomNumberGenerator {
+    fn default() -> LegacyRandomNumberGenerator {
+        LegacyRandomNumberGenerator::new()
     }
 }
 impl RandomNumberGenerator for LegacyRandomNumberGenerator {
     fn random_u32(&mut self, max: u32) -> usize {
-        unsafe { (rand() as u32 % max) as usize }
+        (self.rand() % max) as usize
+    }
+
+    /// Places mines exactly as `winmine.exe` did: draw a column, then a row, and draw again if the square already holds a mine.
+    ///
+    /// `rand()` only returns 15 bits, so boards with a side longer than that fall back to the unbiased shuffle.
+    fn choose_mine_squares(&mut self, num_rows: usize, num_columns: usize, num_mines: usize) -> Vec<usize> {
+        if num_rows > 0x8000 || num_columns > 0x8000 || num_mines >= num_rows * num_columns {
+            return partial_shuffle(self, num_rows * num_columns, num_mines);
+        }
+        let mut is_mine = vec![false; num_rows * num_columns];
+        let mut mines = Vec::with_capacity(num_mines);
+        while mines.len() < num_mines {
+            let column = self.random_u32(num_columns as u32);
+            let row = self.random_u32(num_rows as u32);
+            let index = (row * num_columns) + column;
+            if !is_mine[index] {
+                is_mine[index] = true;
+                mines.push(index);
+            }
+        }
+        mines
     }
 }
 
+/// Generator backed by `rand`'s thread-local RNG.
 pub struct ModernRandomNumberGenerator {
     inner: ThreadRng,
 }
@@ -33,19 +96,36 @@ impl ModernRandomNumberGenerator {
         }
     }
 }
+impl Default for ModernRandomNumberGenerator {
+    fn default() -> ModernRandomNumberGenerator {
+        ModernRandomNumberGenerator::new()
+    }
+}
 impl RandomNumberGenerator for ModernRandomNumberGenerator {
     fn random_u32(&mut self, max: u32) -> usize {
-        self.inner.gen_range(0..=max) as usize
+        self.inner.gen_range(0..max) as usize
     }
 }
 
+/// Generator that produces the same sequence for the same seed on every platform, so boards can be replayed.
+pub struct SeededRandomNumberGenerator {
+    inner: ChaCha8Rng,
+}
+impl SeededRandomNumberGenerator {
+    pub fn new(seed: u64) -> SeededRandomNumberGenerator {
+        SeededRandomNumberGenerator {
+            inner: ChaCha8Rng::seed_from_u64(seed)
+        }
+    }
+}
+impl RandomNumberGenerator for SeededRandomNumberGenerator {
+    fn random_u32(&mut self, max: u32) -> usize {
+        self.inner.gen_range(0..max) as usize
+    }
+}
 
 
 
-extern "C" {
-    fn rand() -> i32;
-    fn srand(seed: u32);
-}
 
 #[cfg(target_os="windows")]
 #[link(name = "Kernel32")]
@@ -56,12 +136,19 @@ extern "C" {
 #[cfg(target_os="linux")]
 fn get_tick_count() -> u32 {
     let time_spec = nix::time::clock_gettime(nix::time::ClockId::CLOCK_MONOTONIC).expect("Could not retrieve TickCount from Linux system");
-    let elapsed_millis = (time_spec.tv_nsec() / 1000) + (time_spec.tv_sec() * 1000);
+    let elapsed_millis = (time_spec.tv_nsec() / 1_000_000) + (time_spec.tv_sec() * 1000);
     elapsed_millis as u32
 }
 
+/// Milliseconds since the Unix epoch stand in for the uptime on other platforms, since only the low word is used as a seed.
+#[cfg(not(any(target_os="windows", target_os="linux")))]
+fn get_tick_count() -> u32 {
+    let elapsed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    elapsed.as_millis() as u32
+}
+
 
 #[inline]
 const fn lo_word(v: u32) -> u16 {
 	(v & 0xffff) as _
-}
\ No newline at end of file
+}
@@ -0,0 +1,125 @@
+//! Checks the WAV decoder and which sounds `Audio` plays as a game goes on, using the recording backend.
+
+use minesweeper_rs::{audio::{self, Audio, AudioBackend, InvalidWav, PlaybackError, RecordingBackend, Sound}, grid::GameState};
+
+/// Builds a PCM WAV file, with an odd-sized chunk before the data to check that padding is skipped.
+fn wav(channels: u16, sample_rate: u32, bits_per_sample: u16, data: &[u8]) -> Vec<u8> {
+    let block_align = channels * bits_per_sample / 8;
+    let mut fmt = Vec::new();
+    fmt.extend_from_slice(&1u16.to_le_bytes());
+    fmt.extend_from_slice(&channels.to_le_bytes());
+    fmt.extend_from_slice(&sample_rate.to_le_bytes());
+    fmt.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    fmt.extend_from_slice(&block_align.to_le_bytes());
+    fmt.extend_from_slice(&bits_per_sample.to_le_bytes());
+
+    let mut body = Vec::new();
+    body.extend_from_slice(b"WAVE");
+    for (id, chunk) in [(b"fmt ", &fmt[..]), (b"note", &[1, 2, 3][..]), (b"data", data)] {
+        body.extend_from_slice(id);
+        body.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        body.extend_from_slice(chunk);
+        if chunk.len() % 2 == 1 {
+            body.push(0);
+        }
+    }
+    let mut file = b"RIFF".to_vec();
+    file.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    file.extend_from_slice(&body);
+    file
+}
+
+#[test]
+fn shipped_sounds_decode() {
+    for (sound, num_frames) in [(Sound::Tick, 1316), (Sound::Explode, 25667), (Sound::Win, 37916)] {
+        let wav = audio::decode_wav(sound.wav()).unwrap();
+        assert_eq!((wav.channels, wav.sample_rate, wav.num_frames()), (1, 22050, num_frames));
+    }
+}
+
+#[test]
+fn widens_unsigned_8_bit_samples() {
+    let wav = audio::decode_wav(&wav(1, 8000, 8, &[128, 255, 0])).unwrap();
+    assert_eq!(wav.samples, [0, 127 << 8, -128 << 8]);
+}
+
+#[test]
+fn decodes_16_bit_stereo() {
+    let data: Vec<u8> = [1i16, -1, i16::MAX, i16::MIN].iter().flat_map(|sample| sample.to_le_bytes()).collect();
+    let wav = audio::decode_wav(&wav(2, 44100, 16, &data)).unwrap();
+    assert_eq!((wav.channels, wav.sample_rate, wav.num_frames()), (2, 44100, 2));
+    assert_eq!(wav.samples, [1, -1, i16::MAX, i16::MIN]);
+}
+
+#[test]
+fn rejects_malformed_wavs() {
+    assert_eq!(audio::decode_wav(b"RIFF\0\0\0\0AVI "), Err(InvalidWav));
+    assert_eq!(audio::decode_wav(&wav(1, 8000, 24, &[0; 6])), Err(InvalidWav));
+    assert_eq!(audio::decode_wav(&wav(0, 8000, 8, &[0; 6])), Err(InvalidWav));
+    //A file cut off before its data chunk.
+    assert_eq!(audio::decode_wav(&audio::TICK_WAV[..40]), Err(InvalidWav));
+}
+
+fn recording_audio(enabled: bool) -> (Audio, RecordingBackend) {
+    let recording = RecordingBackend::new();
+    (Audio::new(Box::new(recording.clone()), enabled), recording)
+}
+
+#[test]
+fn ticks_each_second_then_explodes_once() {
+    let (mut audio, recording) = recording_audio(true);
+    audio.update(GameState::NotStarted, 0);
+    audio.update(GameState::Playing, 1);
+    audio.update(GameState::Playing, 1);
+    audio.update(GameState::Playing, 2);
+    audio.update(GameState::Lost { row: 0, column: 0 }, 2);
+    audio.update(GameState::Lost { row: 0, column: 0 }, 2);
+    assert_eq!(recording.played(), [Sound::Tick, Sound::Tick, Sound::Explode]);
+}
+
+#[test]
+fn plays_fanfare_on_win() {
+    let (mut audio, recording) = recording_audio(true);
+    audio.update(GameState::Playing, 1);
+    audio.update(GameState::Won, 3);
+    audio.update(GameState::Won, 3);
+    //A new game and an immediate win plays the fanfare again.
+    audio.update(GameState::NotStarted, 0);
+    audio.update(GameState::Won, 1);
+    assert_eq!(recording.played(), [Sound::Tick, Sound::Win, Sound::Win]);
+}
+
+#[test]
+fn silent_when_disabled() {
+    let (mut audio, recording) = recording_audio(false);
+    audio.update(GameState::Playing, 1);
+    audio.update(GameState::Lost { row: 0, column: 0 }, 1);
+    audio.play(Sound::Win);
+    assert!(recording.played().is_empty());
+}
+
+/// A backend whose every sound fails, as if the sound card had gone away.
+struct FailingBackend {
+    errors: Vec<PlaybackError>,
+}
+impl AudioBackend for FailingBackend {
+    fn play(&mut self, sound: Sound) {
+        self.errors.push(PlaybackError { message: format!("could not play {sound:?}") });
+    }
+
+    fn take_errors(&mut self) -> Vec<PlaybackError> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+#[test]
+fn playback_errors_are_handed_to_the_frontend() {
+    let mut audio = Audio::new(Box::new(FailingBackend { errors: Vec::new() }), true);
+    audio.play(Sound::Tick);
+    audio.play(Sound::Win);
+    let messages: Vec<String> = audio.take_errors().iter().map(ToString::to_string).collect();
+    assert_eq!(messages, ["could not play Tick", "could not play Win"]);
+    assert!(audio.take_errors().is_empty());
+    //Backends that cannot fail report nothing.
+    assert!(Audio::new(Box::new(RecordingBackend::new()), true).take_errors().is_empty());
+}
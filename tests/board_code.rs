@@ -0,0 +1,86 @@
+//! Checks that board codes survive being written out and read back, forgive the easily confused letters,
+//! and refuse anything that is not a code for a board within the limits.
+
+use minesweeper_rs::{config::{BoardLimits, Config, Difficulty, SafeFirstClick}, grid::GameBoard, BoardCode};
+
+#[test]
+fn codes_survive_a_round_trip() {
+    let difficulties = [Difficulty::BEGINNER, Difficulty::INTERMEDIATE, Difficulty::EXPERT, Difficulty::new(30, 24, 667), Difficulty::new(12, 10, 20)];
+    for difficulty in difficulties {
+        for safe_first_click in [SafeFirstClick::Off, SafeFirstClick::Square, SafeFirstClick::Neighbourhood] {
+            for seed in [0, 1, 0x1234_5678_9ABC_DEF0, u64::MAX] {
+                let board_code = BoardCode::new(difficulty, safe_first_click, seed);
+                assert_eq!(board_code.to_string().parse(), Ok(board_code), "{board_code}");
+            }
+        }
+    }
+}
+
+#[test]
+fn codes_are_written_as_documented() {
+    assert_eq!(BoardCode::new(Difficulty::EXPERT, SafeFirstClick::Off, 31).to_string(), "e-000000000000Z");
+    assert_eq!(BoardCode::new(Difficulty::new(12, 10, 20), SafeFirstClick::Square, 32).to_string(), "12x10x20-0000000000010-s");
+    assert_eq!(BoardCode::new(Difficulty::BEGINNER, SafeFirstClick::Neighbourhood, 0).to_string(), "b-0000000000000-n");
+}
+
+#[test]
+fn codes_ignore_case_and_read_confusable_letters_as_digits() {
+    let board_code = BoardCode::new(Difficulty::INTERMEDIATE, SafeFirstClick::Neighbourhood, 0b00001_00001_00000);
+    for code in ["i-110-n", "I-110-N", "i-IL0-n", "i-il0-n", "i-1lo-n", "i-iLO-N", " i-110-n "] {
+        assert_eq!(code.parse(), Ok(board_code), "{code}");
+    }
+}
+
+#[test]
+fn overlong_seeds_are_refused() {
+    assert_eq!("b-FZZZZZZZZZZZZ".parse::<BoardCode>().map(|board_code| board_code.seed), Ok(u64::MAX));
+    //Fourteen digits, or thirteen that do not fit in 64 bits.
+    assert!("b-00000000000000".parse::<BoardCode>().is_err());
+    assert!("b-G000000000000".parse::<BoardCode>().is_err());
+}
+
+#[test]
+fn garbage_is_refused() {
+    for code in ["", "b", "b-", "-1", "x-1", "b-1U", "b-1*", "b-1-q", "b-1-s-n", "b-1--s", "9x9-1", "9x9x10x1-1", "9xnx10-1", "b-é"] {
+        assert!(code.parse::<BoardCode>().is_err(), "{code:?}");
+    }
+}
+
+#[test]
+fn custom_boards_outside_the_limits_are_refused() {
+    for code in ["100x100x5000-1", "31x9x10-1", "9x8x10-1", "9x9x65-1", "9x9x9-1"] {
+        assert!(code.parse::<BoardCode>().is_err(), "{code}");
+    }
+    //Raised limits let bigger boards through, unchanged.
+    let limits = BoardLimits { max_width: 100, max_height: 100, ..BoardLimits::CLASSIC };
+    let board_code = BoardCode::parse_with_limits("100x100x5000-1", &limits).unwrap();
+    assert_eq!(board_code.difficulty.dimensions(), (100, 100));
+    assert_eq!(board_code.difficulty.num_mines(), 5000);
+}
+
+#[test]
+fn codes_replay_the_same_board_with_a_safe_first_click() {
+    let board_code: BoardCode = "i-1Z4KQ0M9XH3TA-n".parse().unwrap();
+    let config = Config { difficulty: board_code.difficulty, safe_first_click: board_code.safe_first_click, ..Config::default() };
+    let boards: Vec<GameBoard> = (0..2).map(|_| {
+        let mut board = GameBoard::new();
+        board.setup_with_seed(&config, board_code.seed);
+        board.left_click(7, 7).unwrap();
+        board
+    }).collect();
+    assert_eq!(boards[0].grid(), boards[1].grid());
+    //Without the setting the same seed lays out a different board.
+    let mut board = GameBoard::new();
+    board.setup_with_seed(&Config { safe_first_click: SafeFirstClick::Off, ..config }, board_code.seed);
+    board.left_click(7, 7).unwrap();
+    assert_ne!(board.grid(), boards[0].grid());
+}
+
+#[test]
+fn max_length_fits_the_longest_code_within_the_limits() {
+    for limits in [BoardLimits::CLASSIC, BoardLimits { max_width: 1000, max_height: 1000, ..BoardLimits::CLASSIC }] {
+        let difficulty = Difficulty::try_with_limits(limits.max_width, limits.max_height, limits.max_mines(), &limits).unwrap();
+        let longest = BoardCode::new(difficulty, SafeFirstClick::Neighbourhood, 0).to_string();
+        assert_eq!(BoardCode::max_length(&limits), longest.len(), "{longest}");
+    }
+}
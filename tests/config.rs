@@ -0,0 +1,150 @@
+//! Checks validation of custom boards and the limits read from `config.ini`, the zoom setting, and the key bindings and how they are read from and written to the `[keys]` section of `config.ini`.
+
+use ini::Ini;
+use minesweeper_rs::{config::{BoardLimits, Config, Difficulty, DifficultyType, HighScore, InvalidDifficulty, KeyAction, KeyBindings, Scale}, grid::GameBoard};
+
+/// A complete `config.ini` for a custom board of `size`, with `extra` lines added to the general section.
+fn config_ini(size: &str, extra: &str) -> Ini {
+    let [columns, rows, mines]: [&str; 3] = size.split('x').collect::<Vec<_>>().try_into().unwrap();
+    let text = format!("graphics_type = opengl\nlegacy_rng = false\ndifficulty = custom\nnum_columns = {columns}\nnum_rows = {rows}\nnum_mines = {mines}\n\
+        window_pos_x = 80\nwindow_pos_y = 80\nsound_enabled = false\nmarks_enabled = true\ncolour_enabled = true\n\
+        beginner_name = Anonymous\nbeginner_time = 999\nintermediate_name = Anonymous\nintermediate_time = 999\nexpert_name = Anonymous\nexpert_time = 999\n{extra}");
+    Ini::load_from_str(&text).unwrap()
+}
+
+#[test]
+fn board_limits_default_to_the_classic_game() {
+    let config = Config::from_ini(&config_ini("50x40x100", "")).unwrap();
+    assert_eq!(config.board_limits, BoardLimits::CLASSIC);
+    assert_eq!(config.difficulty.dimensions(), (30, 24));
+}
+
+#[test]
+fn board_limits_are_read_from_max_rows_and_max_columns() {
+    let config = Config::from_ini(&config_ini("50x40x100", "max_columns = 60\nmax_rows = 40\n")).unwrap();
+    assert_eq!(config.board_limits, BoardLimits { max_width: 60, max_height: 40, ..BoardLimits::CLASSIC });
+    assert_eq!(config.difficulty.dimensions(), (50, 40));
+    assert_eq!(config.difficulty.num_mines(), 100);
+    let mut board = GameBoard::new();
+    board.setup_with_seed(&config, 1);
+    assert_eq!((board.num_columns(), board.num_rows()), (50, 40));
+    //Only the maximums can be raised.
+    let config = Config::from_ini(&config_ini("5x5x10", "max_columns = 60\nmax_rows = 40\n")).unwrap();
+    assert_eq!(config.difficulty.dimensions(), (9, 9));
+}
+
+#[test]
+fn invalid_board_limits_are_refused() {
+    assert!(Config::from_ini(&config_ini("9x9x10", "max_rows = lots\n")).is_err());
+}
+
+#[test]
+fn custom_boards_within_limits_are_accepted_unchanged() {
+    let difficulty = Difficulty::try_with_limits(30, 24, 667, &BoardLimits::CLASSIC).unwrap();
+    assert_eq!(difficulty.difficulty_type(), DifficultyType::Custom);
+    assert_eq!(difficulty.dimensions(), (30, 24));
+    assert_eq!(difficulty.num_mines(), 667);
+    assert_eq!(difficulty, Difficulty::with_limits(30, 24, 667, &BoardLimits::CLASSIC));
+}
+
+#[test]
+fn custom_boards_outside_limits_are_refused() {
+    let limits = BoardLimits::CLASSIC;
+    assert_eq!(Difficulty::try_with_limits(31, 9, 10, &limits), Err(InvalidDifficulty::Width { min: 9, max: 30 }));
+    assert_eq!(Difficulty::try_with_limits(9, 8, 10, &limits), Err(InvalidDifficulty::Height { min: 9, max: 24 }));
+    assert_eq!(Difficulty::try_with_limits(9, 9, 65, &limits), Err(InvalidDifficulty::Mines { min: 10, max: 64 }));
+    assert_eq!(Difficulty::try_with_limits(9, 9, 9, &limits), Err(InvalidDifficulty::Mines { min: 10, max: 64 }));
+    assert_eq!(InvalidDifficulty::Height { min: 9, max: 24 }.to_string(), "height must be 9 to 24");
+}
+
+#[test]
+fn only_faster_wins_at_a_standard_difficulty_are_recorded() {
+    let mut config = Config { difficulty: Difficulty::INTERMEDIATE, ..Config::default() };
+    assert!(config.record_time("Ada", 120));
+    assert_eq!(config.intermediate_score, HighScore { name: String::from("Ada"), time: 120 });
+    assert!(!config.record_time("Grace", 120));
+    assert!(config.record_time("Grace", 95));
+    assert_eq!(config.intermediate_score, HighScore { name: String::from("Grace"), time: 95 });
+    assert_eq!((config.beginner_score.clone(), config.expert_score.clone()), (HighScore::default(), HighScore::default()));
+
+    config.difficulty = Difficulty::with_limits(20, 20, 50, &config.board_limits);
+    assert!(!config.record_time("Ada", 1));
+}
+
+#[test]
+fn auto_scale_rounds_the_scale_factor() {
+    assert_eq!(Scale::Auto.resolve(1.0), 1);
+    assert_eq!(Scale::Auto.resolve(1.25), 1);
+    assert_eq!(Scale::Auto.resolve(1.5), 2);
+    assert_eq!(Scale::Auto.resolve(0.5), 1);
+    assert_eq!(Scale::Auto.resolve(6.0), Scale::MAX);
+    assert_eq!(Scale::Fixed(3).resolve(1.0), 3);
+}
+
+#[test]
+fn fixed_scale_stays_within_limits() {
+    assert_eq!(Scale::fixed(0), None);
+    assert_eq!(Scale::fixed(4), Some(Scale::Fixed(4)));
+    assert_eq!(Scale::fixed(5), None);
+    assert_eq!(Scale::zoom_in(2), Scale::Fixed(3));
+    assert_eq!(Scale::zoom_in(Scale::MAX), Scale::Fixed(Scale::MAX));
+    assert_eq!(Scale::zoom_out(2), Scale::Fixed(1));
+    assert_eq!(Scale::zoom_out(1), Scale::Fixed(1));
+}
+
+#[test]
+fn scale_is_written_as_auto_or_a_number() {
+    assert_eq!(Scale::Auto.to_string(), "auto");
+    assert_eq!(Scale::Fixed(2).to_string(), "2");
+}
+
+#[test]
+fn default_bindings_cover_arrows_wasd_and_hjkl() {
+    let key_bindings = KeyBindings::default();
+    for (keys, action) in [(["Up", "W", "K"], KeyAction::Up), (["Down", "S", "J"], KeyAction::Down), (["Left", "A", "H"], KeyAction::Left), (["Right", "D", "L"], KeyAction::Right)] {
+        for key in keys {
+            assert_eq!(key_bindings.action_for(key), Some(action));
+        }
+    }
+    assert_eq!(key_bindings.action_for("F2"), Some(KeyAction::NewGame));
+    assert_eq!(key_bindings.action_for("Key3"), Some(KeyAction::Expert));
+    assert_eq!(key_bindings.action_for("F3"), None);
+}
+
+#[test]
+fn keys_match_without_regard_to_case() {
+    let key_bindings = KeyBindings::default();
+    assert_eq!(key_bindings.action_for("space"), Some(KeyAction::Reveal));
+    assert_eq!(key_bindings.action_for("f2"), Some(KeyAction::NewGame));
+}
+
+#[test]
+fn missing_section_keeps_defaults() {
+    assert_eq!(KeyBindings::from_ini(&Ini::new()), KeyBindings::default());
+}
+
+#[test]
+fn section_overrides_and_unbinds_actions() {
+    let ini = Ini::load_from_str("[keys]\nreveal = Z ,  Return\nflag =\n").unwrap();
+    let key_bindings = KeyBindings::from_ini(&ini);
+    assert_eq!(key_bindings.keys(KeyAction::Reveal), ["Z", "Return"]);
+    assert!(key_bindings.keys(KeyAction::Flag).is_empty());
+    assert_eq!(key_bindings.action_for("Space"), None);
+    assert_eq!(key_bindings.action_for("F"), None);
+    //Actions left out of the section keep their defaults.
+    assert_eq!(key_bindings.keys(KeyAction::Chord), KeyBindings::default().keys(KeyAction::Chord));
+}
+
+#[test]
+fn bindings_survive_a_round_trip() {
+    let mut key_bindings = KeyBindings::default();
+    key_bindings.set_keys(KeyAction::Chord, vec![String::from("Q"), String::from("E")]);
+    key_bindings.set_keys(KeyAction::Colour, Vec::new());
+    let mut ini = Ini::new();
+    key_bindings.write_to_ini(&mut ini);
+    let mut written = Vec::new();
+    ini.write_to(&mut written).unwrap();
+    let ini = Ini::load_from_str(std::str::from_utf8(&written).unwrap()).unwrap();
+    assert_eq!(ini.get_from(Some("keys"), "chord"), Some("Q, E"));
+    assert_eq!(KeyBindings::from_ini(&ini), key_bindings);
+}
@@ -0,0 +1,34 @@
+//! Checks that drawing into the software renderer's framebuffer is clipped to its edges.
+
+use minesweeper_rs::framebuffer::Framebuffer;
+
+const RED: u32 = 0xFF0000;
+
+#[test]
+fn rects_are_clipped_to_the_edges() {
+    let mut framebuffer = Framebuffer::new(4, 3);
+    framebuffer.fill_rect(-1, 2, 3, 5, RED);
+    assert_eq!(framebuffer.pixels, [
+        0,   0,   0, 0,
+        0,   0,   0, 0,
+        RED, RED, 0, 0,
+    ]);
+    framebuffer.clear(0);
+    framebuffer.fill_rect(3, -2, 9, 3, RED);
+    assert_eq!(framebuffer.pixels, [
+        0, 0, 0, RED,
+        0, 0, 0, 0,
+        0, 0, 0, 0,
+    ]);
+}
+
+#[test]
+fn rects_off_the_edges_draw_nothing() {
+    //As when the board grows before the window does.
+    let mut framebuffer = Framebuffer::new(168, 225);
+    for (x, y) in [(200, 10), (10, 300), (200, 300), (168, 0), (0, 225), (-20, 10), (10, -20)] {
+        framebuffer.fill_rect(x, y, 16, 16, RED);
+    }
+    framebuffer.fill_rect(5, 5, 0, 10, RED);
+    assert!(framebuffer.pixels.iter().all(|&pixel| pixel == 0));
+}
@@ -0,0 +1,324 @@
+//! Checks the rules of play on a small board with a known layout: chording, flags and marks, how a game
+//! ends, and what each reveal reports. A huge board checks that one reveal can open a million squares.
+
+use minesweeper_rs::{config::{BoardLimits, Config, Difficulty}, grid::{GameBoard, GameState}, util::RandomNumberGenerator};
+
+/// Places mines on exactly the squares it is given.
+struct FixedMines(Vec<(usize, usize)>);
+impl RandomNumberGenerator for FixedMines {
+    fn random_u32(&mut self, _max: u32) -> usize {
+        unreachable!("mines are chosen all at once")
+    }
+
+    fn choose_mine_squares(&mut self, _num_rows: usize, num_columns: usize, _num_mines: usize) -> Vec<usize> {
+        self.0.iter().map(|&(row, column)| (row * num_columns) + column).collect()
+    }
+}
+
+/// A 9x9 board with a mine at (0, 1) and a column of mines down the right hand side:
+///
+/// ```text
+/// 1M1....2M
+/// 111....3M
+/// .......3M
+///    ...
+/// .......2M
+/// ```
+///
+/// Uncovering any blank square opens every safe square but (0, 0), which only borders numbers and the mine.
+fn board() -> GameBoard {
+    let config = Config { difficulty: Difficulty::new(9, 9, 10), ..Config::default() };
+    let mut mines = vec![(0, 1)];
+    mines.extend((0..9).map(|row| (row, 8)));
+    let mut board = GameBoard::new();
+    board.setup(&config, &mut FixedMines(mines));
+    board
+}
+
+#[test]
+fn chord_opens_neighbours_when_flags_match_the_number() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    board.right_click(0, 1).unwrap();
+    board.chord(0, 0).unwrap();
+    assert!(board.is_visited(1, 0).unwrap());
+    assert!(board.is_visited(1, 1).unwrap());
+    assert!(!board.is_visited(0, 1).unwrap());
+    assert!(board.is_flagged(0, 1).unwrap());
+}
+
+#[test]
+fn chord_does_nothing_when_flags_do_not_match() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    assert!(board.chord(0, 0).unwrap().changed().is_empty());
+    assert!(!board.is_visited(1, 0).unwrap());
+    assert!(!board.is_visited(1, 1).unwrap());
+    //Too many flags is a mismatch as well.
+    board.right_click(0, 1).unwrap();
+    board.right_click(1, 1).unwrap();
+    assert!(board.chord(0, 0).unwrap().changed().is_empty());
+    assert!(!board.is_visited(1, 0).unwrap());
+}
+
+#[test]
+fn chord_cascades_through_blank_neighbours() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    board.right_click(0, 1).unwrap();
+    board.chord(0, 0).unwrap();
+    //(1, 0) borders the blank square (2, 0), which opens the rest of the board.
+    board.chord(1, 0).unwrap();
+    assert!(board.is_visited(2, 0).unwrap());
+    assert!(board.is_visited(8, 7).unwrap());
+    assert!(!board.is_visited(8, 8).unwrap());
+}
+
+#[test]
+fn chord_with_a_wrong_flag_loses() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    board.right_click(1, 1).unwrap();
+    board.chord(0, 0).unwrap();
+    assert_eq!(board.game_state(), GameState::Lost { row: 0, column: 1 });
+    //The other unflagged neighbour is uncovered too, as in the original game.
+    assert!(board.is_visited(1, 0).unwrap());
+}
+
+#[test]
+fn right_click_cycles_flag_mark_and_covered() {
+    let mut board = board();
+    board.right_click(4, 4).unwrap();
+    assert!(board.is_flagged(4, 4).unwrap());
+    assert_eq!(board.num_flags(), 1);
+    board.right_click(4, 4).unwrap();
+    assert!(!board.is_flagged(4, 4).unwrap());
+    assert!(board.is_q_marked(4, 4).unwrap());
+    assert_eq!(board.num_flags(), 0);
+    board.right_click(4, 4).unwrap();
+    assert!(!board.is_flagged(4, 4).unwrap());
+    assert!(!board.is_q_marked(4, 4).unwrap());
+    assert_eq!(board.num_flags(), 0);
+}
+
+#[test]
+fn right_click_skips_the_mark_when_marks_are_disabled() {
+    let mut board = board();
+    board.set_marks_enabled(false);
+    board.right_click(4, 4).unwrap();
+    board.right_click(4, 4).unwrap();
+    assert!(!board.is_flagged(4, 4).unwrap());
+    assert!(!board.is_q_marked(4, 4).unwrap());
+    assert_eq!(board.num_flags(), 0);
+}
+
+#[test]
+fn num_flags_counts_every_flag() {
+    let mut board = board();
+    for column in 0..5 {
+        board.right_click(3, column).unwrap();
+    }
+    assert_eq!(board.num_flags(), 5);
+    board.right_click(3, 0).unwrap();
+    assert_eq!(board.num_flags(), 4);
+}
+
+#[test]
+fn right_click_does_nothing_on_uncovered_squares() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    board.right_click(0, 0).unwrap();
+    assert!(!board.is_flagged(0, 0).unwrap());
+    assert_eq!(board.num_flags(), 0);
+}
+
+#[test]
+fn right_click_does_nothing_after_the_game_ends() {
+    let mut board = board();
+    board.left_click(0, 1).unwrap();
+    board.right_click(4, 4).unwrap();
+    assert!(!board.is_flagged(4, 4).unwrap());
+    assert_eq!(board.num_flags(), 0);
+}
+
+#[test]
+fn first_reveal_starts_the_game() {
+    let mut board = board();
+    assert_eq!(board.game_state(), GameState::NotStarted);
+    board.right_click(4, 4).unwrap();
+    assert_eq!(board.game_state(), GameState::NotStarted);
+    board.right_click(4, 4).unwrap();
+    board.right_click(4, 4).unwrap();
+    board.left_click(0, 0).unwrap();
+    assert_eq!(board.game_state(), GameState::Playing);
+    assert!(!board.game_state().is_over());
+}
+
+#[test]
+fn revealing_a_mine_loses() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    board.left_click(0, 1).unwrap();
+    assert_eq!(board.game_state(), GameState::Lost { row: 0, column: 1 });
+    assert!(board.game_state().is_over());
+}
+
+#[test]
+fn revealing_every_safe_square_wins() {
+    let mut board = board();
+    board.left_click(2, 0).unwrap();
+    assert_eq!(board.game_state(), GameState::Playing);
+    assert_eq!(board.num_uncovered_squares(), 1);
+    board.left_click(0, 0).unwrap();
+    assert_eq!(board.game_state(), GameState::Won);
+    assert_eq!(board.num_uncovered_squares(), 0);
+}
+
+#[test]
+fn input_is_ignored_once_the_game_is_over() {
+    let mut board = board();
+    board.left_click(2, 0).unwrap();
+    board.left_click(0, 1).unwrap();
+    for outcome in [board.left_click(0, 0).unwrap(), board.chord(1, 0).unwrap()] {
+        assert!(outcome.changed().is_empty());
+    }
+    assert!(!board.is_visited(0, 0).unwrap());
+    assert_eq!(board.game_state(), GameState::Lost { row: 0, column: 1 });
+}
+
+#[test]
+fn winning_flags_the_remaining_mines() {
+    let mut board = board();
+    board.right_click(0, 8).unwrap();
+    //A question mark on a mine gives way to a flag.
+    board.right_click(1, 8).unwrap();
+    board.right_click(1, 8).unwrap();
+    board.left_click(2, 0).unwrap();
+    let outcome = board.left_click(0, 0).unwrap();
+    assert_eq!(board.game_state(), GameState::Won);
+    assert_eq!(board.num_flags(), board.num_mines());
+    for row in 0..9 {
+        assert!(board.is_flagged(row, 8).unwrap());
+        assert!(!board.is_q_marked(row, 8).unwrap());
+    }
+    assert!(board.is_flagged(0, 1).unwrap());
+    //Every newly flagged mine is reported as changed, but not the one that was already flagged.
+    assert!(outcome.changed().contains(&(1, 8)));
+    assert!(!outcome.changed().contains(&(0, 8)));
+}
+
+#[test]
+fn single_reveal_reports_one_square() {
+    let mut board = board();
+    let outcome = board.left_click(0, 0).unwrap();
+    assert_eq!(outcome.changed(), [(0, 0)]);
+    assert_eq!(outcome.num_uncovered(), 1);
+    assert_eq!(outcome.mine_hit(), None);
+    //Uncovering it again changes nothing.
+    assert_eq!(board.left_click(0, 0).unwrap().changed(), []);
+}
+
+#[test]
+fn cascade_reports_every_square_it_opens() {
+    let mut board = board();
+    let outcome = board.left_click(2, 0).unwrap();
+    assert_eq!(outcome.changed()[0], (2, 0));
+    assert_eq!(outcome.changed().len(), 70);
+    assert_eq!(outcome.num_uncovered(), 70);
+    assert!(!outcome.hit_mine());
+}
+
+#[test]
+fn mine_hit_reports_the_mine() {
+    let mut board = board();
+    let outcome = board.left_click(0, 1).unwrap();
+    assert_eq!(outcome.changed(), [(0, 1)]);
+    assert_eq!(outcome.num_uncovered(), 0);
+    assert_eq!(outcome.mine_hit(), Some((0, 1)));
+}
+
+#[test]
+fn chord_reports_the_neighbours_it_opens() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    board.right_click(0, 1).unwrap();
+    let outcome = board.chord(0, 0).unwrap();
+    assert_eq!(outcome.changed(), [(1, 0), (1, 1)]);
+    assert_eq!(outcome.num_uncovered(), 2);
+    assert_eq!(outcome.mine_hit(), None);
+}
+
+#[test]
+fn chord_onto_a_mine_reports_the_mine() {
+    let mut board = board();
+    board.left_click(0, 0).unwrap();
+    board.right_click(1, 1).unwrap();
+    let outcome = board.chord(0, 0).unwrap();
+    assert_eq!(outcome.changed(), [(0, 1), (1, 0)]);
+    assert_eq!(outcome.num_uncovered(), 1);
+    assert_eq!(outcome.mine_hit(), Some((0, 1)));
+}
+
+#[test]
+fn a_reveal_cascades_across_a_huge_board_on_a_small_stack() {
+    //A recursive fill would need a stack frame for each of the million squares.
+    let reveal = std::thread::Builder::new().stack_size(256 * 1024).spawn(|| {
+        let limits = BoardLimits { max_width: 1000, max_height: 1000, ..BoardLimits::CLASSIC };
+        let config = Config { difficulty: Difficulty::with_limits(1000, 1000, 10, &limits), ..Config::default() };
+        let mut board = GameBoard::new();
+        //Ten mines along the bottom edge in the right hand corner.
+        board.setup(&config, &mut FixedMines((990..1000).map(|column| (999, column)).collect()));
+        let outcome = board.left_click(0, 0).unwrap();
+        (outcome.num_uncovered(), board.num_uncovered_squares(), board.game_state())
+    }).unwrap();
+    assert_eq!(reveal.join().unwrap(), (1000 * 1000 - 10, 0, GameState::Won));
+}
+
+#[test]
+fn every_accessor_refuses_squares_off_the_board() {
+    type Accessor = fn(&mut GameBoard, usize, usize) -> bool;
+    let accessors: [(&str, Accessor); 24] = [
+        ("index", |board, row, column| board.index(row, column).is_err()),
+        ("get_display", |board, row, column| board.get_display(row, column).is_err()),
+        ("get_square", |board, row, column| board.get_square(row, column).is_err()),
+        ("square", |board, row, column| board.square(row, column).is_err()),
+        ("is_visited", |board, row, column| board.is_visited(row, column).is_err()),
+        ("is_mine", |board, row, column| board.is_mine(row, column).is_err()),
+        ("is_flagged", |board, row, column| board.is_flagged(row, column).is_err()),
+        ("is_q_marked", |board, row, column| board.is_q_marked(row, column).is_err()),
+        ("set_visited", |board, row, column| board.set_visited(row, column).is_err()),
+        ("set_mine", |board, row, column| board.set_mine(row, column).is_err()),
+        ("set_flagged", |board, row, column| board.set_flagged(row, column).is_err()),
+        ("set_q_marked", |board, row, column| board.set_q_marked(row, column).is_err()),
+        ("clear_flagged", |board, row, column| board.clear_flagged(row, column).is_err()),
+        ("clear_q_marked", |board, row, column| board.clear_q_marked(row, column).is_err()),
+        ("get_adjacent", |board, row, column| board.get_adjacent(row, column).is_err()),
+        ("set_adjacent", |board, row, column| board.set_adjacent(row, column, 1).is_err()),
+        ("mask_matches_exact", |board, row, column| board.mask_matches_exact(row, column, 0).is_err()),
+        ("mask_matches_any", |board, row, column| board.mask_matches_any(row, column, 0).is_err()),
+        ("adjacent_square_indices", |board, row, column| board.adjacent_square_indices(row, column).is_err()),
+        ("adjacent_square_indices_no_diag", |board, row, column| board.adjacent_square_indices_no_diag(row, column).is_err()),
+        ("uncover_adjacent_empty_squares", |board, row, column| board.uncover_adjacent_empty_squares(row, column, &mut Vec::new()).is_err()),
+        ("left_click", |board, row, column| board.left_click(row, column).is_err()),
+        ("right_click", |board, row, column| board.right_click(row, column).is_err()),
+        ("chord", |board, row, column| board.chord(row, column).is_err()),
+    ];
+    let untouched = board().grid().to_vec();
+    for (name, is_err) in accessors {
+        let mut board = board();
+        for (row, column) in [(9, 0), (0, 9), (9, 9), (usize::MAX, 0)] {
+            assert!(is_err(&mut board, row, column), "{name}({row}, {column})");
+        }
+        //A refused call leaves the board as it was.
+        assert_eq!(board.grid(), untouched, "{name}");
+        assert_eq!(board.game_state(), GameState::NotStarted, "{name}");
+        assert!(!is_err(&mut board, 8, 8), "{name}(8, 8)");
+    }
+}
+
+#[test]
+fn out_of_bounds_describes_the_square_and_the_board() {
+    let error = board().index(9, 2).unwrap_err();
+    assert_eq!((error.square(), error.board_size()), ((9, 2), (9, 9)));
+    assert_eq!(error.to_string(), "square (9, 2) is outside the 9x9 board");
+}
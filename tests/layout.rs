@@ -0,0 +1,76 @@
+//! Checks where the parts of the window go and how cursor positions, at any scale, map onto them.
+
+use minesweeper_rs::{config::{Config, Difficulty}, grid::GameBoard, layout::{self, Layout, CELL_SIZE}, util::ModernRandomNumberGenerator};
+
+fn layout(difficulty: Difficulty) -> Layout {
+    let mut board = GameBoard::new();
+    board.setup(&Config { difficulty, ..Config::default() }, &mut ModernRandomNumberGenerator::new());
+    Layout::new(&board)
+}
+
+/// The square under a cursor at `position` in physical pixels, with everything drawn `scale` times its size.
+fn square_at(layout: &Layout, position: (f64, f64), scale: u32) -> Option<(usize, usize)> {
+    layout.square_at(layout::to_layout_position(position, scale))
+}
+
+#[test]
+fn beginner_window_fits_the_board_below_the_menu_bar_and_header() {
+    let layout = layout(Difficulty::BEGINNER);
+    assert_eq!((layout.width, layout.height), (168, 225));
+    assert_eq!(layout.board, (12, 69));
+    assert_eq!(layout.board_size, (9 * CELL_SIZE, 9 * CELL_SIZE));
+    assert_eq!(layout.face, (72, 30));
+    assert!(layout.is_over_face(layout.face));
+    assert!(!layout.is_over_face((layout.face.0 - 1, layout.face.1)));
+}
+
+#[test]
+fn positions_map_to_squares_at_scale_1() {
+    let layout = layout(Difficulty::BEGINNER);
+    assert_eq!(square_at(&layout, (12.0, 69.0), 1), Some((0, 0)));
+    assert_eq!(square_at(&layout, (27.9, 84.9), 1), Some((0, 0)));
+    assert_eq!(square_at(&layout, (28.0, 85.0), 1), Some((1, 1)));
+    assert_eq!(square_at(&layout, (155.0, 212.0), 1), Some((8, 8)));
+}
+
+#[test]
+fn positions_map_to_squares_at_scale_2() {
+    let layout = layout(Difficulty::BEGINNER);
+    assert_eq!(square_at(&layout, (24.0, 138.0), 2), Some((0, 0)));
+    assert_eq!(square_at(&layout, (55.9, 169.9), 2), Some((0, 0)));
+    assert_eq!(square_at(&layout, (56.0, 170.0), 2), Some((1, 1)));
+    assert_eq!(square_at(&layout, (311.9, 425.9), 2), Some((8, 8)));
+    //Just outside the board, which would be inside it at scale 1.
+    assert_eq!(square_at(&layout, (23.9, 138.0), 2), None);
+    assert_eq!(square_at(&layout, (312.0, 425.0), 2), None);
+}
+
+#[test]
+fn header_and_borders_are_not_the_board() {
+    let layout = layout(Difficulty::BEGINNER);
+    //The header above the first row, and the left border beside it.
+    assert_eq!(square_at(&layout, (12.0, 68.9), 1), None);
+    assert_eq!(square_at(&layout, (11.9, 69.0), 1), None);
+    assert_eq!(square_at(&layout, (layout.face.0 as f64, layout.face.1 as f64), 1), None);
+    //The menu bar, and positions to the left of or above the window.
+    assert_eq!(square_at(&layout, (20.0, 5.0), 1), None);
+    assert_eq!(square_at(&layout, (-0.5, 70.0), 1), None);
+    assert_eq!(square_at(&layout, (20.0, -1.0), 1), None);
+}
+
+#[test]
+fn positions_past_the_last_column_or_row_are_not_the_board() {
+    let layout = layout(Difficulty::EXPERT);
+    let (right, bottom) = ((layout.board.0 as u32 + layout.board_size.0) as f64, (layout.board.1 as u32 + layout.board_size.1) as f64);
+    assert_eq!(square_at(&layout, (right - 1.0, bottom - 1.0), 1), Some((15, 29)));
+    assert_eq!(square_at(&layout, (right, bottom - 1.0), 1), None);
+    assert_eq!(square_at(&layout, (right - 1.0, bottom), 1), None);
+    assert_eq!(square_at(&layout, (layout.width as f64 + 10.0, 100.0), 1), None);
+}
+
+#[test]
+fn layout_positions_round_down() {
+    assert_eq!(layout::to_layout_position((25.0, 37.0), 1), (25, 37));
+    assert_eq!(layout::to_layout_position((25.0, 37.0), 2), (12, 18));
+    assert_eq!(layout::to_layout_position((-1.0, 0.5), 3), (-1, 0));
+}
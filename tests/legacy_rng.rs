@@ -0,0 +1,76 @@
+//! Checks that `LegacyRandomNumberGenerator` matches the Microsoft C runtime and places mines as `winmine.exe` did.
+//!
+//! The expected values were worked out from the runtime's generator itself, `state = state * 214013 + 2531011`
+//! returning `(state >> 16) & 0x7fff`, rather than taken from the code under test.
+
+use minesweeper_rs::{config::{Config, Difficulty}, grid::{GameBoard, GameState}, util::LegacyRandomNumberGenerator};
+
+fn rand_sequence(seed: u32) -> Vec<u32> {
+    let mut rng = LegacyRandomNumberGenerator::with_seed(seed);
+    (0..12).map(|_| rng.rand()).collect()
+}
+
+fn legacy_board(seed: u32) -> GameBoard {
+    let config = Config { difficulty: Difficulty::BEGINNER, ..Config::default() };
+    let mut board = GameBoard::new();
+    board.setup_legacy(&config, &mut LegacyRandomNumberGenerator::with_seed(seed));
+    board
+}
+
+/// The squares holding mines, row by row.
+fn mines(board: &GameBoard) -> Vec<(usize, usize)> {
+    (0..board.num_rows())
+        .flat_map(|row| (0..board.num_columns()).map(move |column| (row, column)))
+        .filter(|&(row, column)| board.is_mine(row, column).unwrap())
+        .collect()
+}
+
+#[test]
+fn rand_matches_msvc_sequence() {
+    assert_eq!(rand_sequence(0), [38, 7719, 21238, 2437, 8855, 11797, 8365, 32285, 10450, 30612, 5853, 28100]);
+    assert_eq!(rand_sequence(1), [41, 18467, 6334, 26500, 19169, 15724, 11478, 29358, 26962, 24464, 5705, 28145]);
+    assert_eq!(rand_sequence(1234), [4068, 213, 12761, 8758, 23056, 7717, 15274, 24508, 4056, 13304, 19945, 16918]);
+    assert_eq!(rand_sequence(0xBEEF), [28584, 9731, 2411, 18557, 6490, 24250, 34, 15104, 4632, 1769, 18075, 18369]);
+}
+
+#[test]
+fn beginner_mines_are_drawn_column_then_row() {
+    //After srand(0), rand() % 9 gives 2 6, 7 7, 8 7, 4 2, 1 3, 3 2, 8 2, 8 0, 8 5, 0 1 as column then row.
+    let expected = [(0, 8), (1, 0), (2, 3), (2, 4), (2, 8), (3, 1), (5, 8), (6, 2), (7, 7), (7, 8)];
+    assert_eq!(mines(&legacy_board(0)), expected);
+}
+
+#[test]
+fn a_square_drawn_twice_is_drawn_again() {
+    //After srand(2), rand() % 9 gives 0 2, 6 2, 0 3, 1 7, 4 1, 4 1, 7 3, 5 0, 5 1, 7 8, 5 7 as column then row.
+    //Row 1, column 4 comes up twice, so eleven draws are needed for ten mines.
+    let expected = [(0, 5), (1, 4), (1, 5), (2, 0), (2, 6), (3, 0), (3, 7), (7, 1), (7, 5), (8, 7)];
+    assert_eq!(mines(&legacy_board(2)), expected);
+}
+
+#[test]
+fn the_first_click_moves_a_mine_to_the_first_free_square() {
+    let mut board = legacy_board(0);
+    assert!(board.is_mine(6, 2).unwrap());
+
+    let outcome = board.left_click(6, 2).unwrap();
+    assert!(!outcome.hit_mine());
+    assert_eq!(board.game_state(), GameState::Playing);
+    assert!(!board.is_mine(6, 2).unwrap());
+    assert!(board.is_mine(0, 0).unwrap());
+    assert_eq!(mines(&board).len(), 10);
+    assert_eq!(board.get_adjacent(1, 1).unwrap(), 2);
+}
+
+#[test]
+fn only_the_first_click_moves_a_mine() {
+    let mut board = legacy_board(0);
+    board.left_click(0, 0).unwrap();
+    assert_eq!(board.left_click(6, 2).unwrap().mine_hit(), Some((6, 2)));
+
+    //A board set up with the same generator through setup() keeps the mine where it was drawn.
+    let config = Config { difficulty: Difficulty::BEGINNER, ..Config::default() };
+    let mut board = GameBoard::new();
+    board.setup(&config, &mut LegacyRandomNumberGenerator::with_seed(0));
+    assert_eq!(board.left_click(6, 2).unwrap().mine_hit(), Some((6, 2)));
+}
@@ -0,0 +1,100 @@
+//! Statistical checks that mine placement is uniform and always places the requested number of mines.
+
+use minesweeper_rs::{config::{Config, Difficulty, SafeFirstClick}, grid::GameBoard, util::{ModernRandomNumberGenerator, RandomNumberGenerator}};
+
+const NUM_BOARDS: u64 = 20_000;
+
+/// Upper 0.1% points of the chi-squared distribution, so a correct generator fails about one run in a thousand seeds.
+const CHI_SQUARED_80: f64 = 124.84;
+const CHI_SQUARED_71: f64 = 113.61;
+
+fn chi_squared(counts: &[u64], expected: f64) -> f64 {
+    counts.iter().map(|&count| (count as f64 - expected).powi(2) / expected).sum()
+}
+
+fn count_mines(board: &GameBoard, counts: &mut [u64]) -> usize {
+    let mut num_mines = 0;
+    for (index, count) in counts.iter_mut().enumerate() {
+        if board.is_mine(index / board.num_columns(), index % board.num_columns()).unwrap() {
+            *count += 1;
+            num_mines += 1;
+        }
+    }
+    num_mines
+}
+
+#[test]
+fn every_density_places_every_mine() {
+    //Difficulty::new allows at most (9 - 1) * (9 - 1) mines on the smallest board.
+    for num_mines in 10..=64 {
+        let config = Config { difficulty: Difficulty::new(9, 9, num_mines), ..Config::default() };
+        for seed in 0..50 {
+            let mut board = GameBoard::new();
+            board.setup_with_seed(&config, seed);
+            assert_eq!(count_mines(&board, &mut [0; 81]), num_mines as usize);
+        }
+    }
+}
+
+#[test]
+fn every_density_keeps_first_click_safe() {
+    for num_mines in 10..=64 {
+        let config = Config { difficulty: Difficulty::new(9, 9, num_mines), safe_first_click: SafeFirstClick::Neighbourhood, ..Config::default() };
+        for seed in 0..50 {
+            let mut board = GameBoard::new();
+            board.setup_with_seed(&config, seed);
+            assert!(!board.left_click(4, 4).unwrap().hit_mine());
+            assert_eq!(count_mines(&board, &mut [0; 81]), num_mines as usize);
+        }
+    }
+}
+
+#[test]
+fn beginner_mines_are_uniform() {
+    let config = Config::default();
+    let mut counts = [0; 81];
+    for seed in 0..NUM_BOARDS {
+        let mut board = GameBoard::new();
+        board.setup_with_seed(&config, seed);
+        count_mines(&board, &mut counts);
+    }
+    let expected = NUM_BOARDS as f64 * 10.0 / 81.0;
+    assert!(chi_squared(&counts, expected) < CHI_SQUARED_80, "{counts:?}");
+}
+
+#[test]
+fn densest_mines_are_uniform() {
+    let config = Config { difficulty: Difficulty::new(9, 9, 64), ..Config::default() };
+    let mut counts = [0; 81];
+    for seed in 0..NUM_BOARDS {
+        let mut board = GameBoard::new();
+        board.setup_with_seed(&config, seed);
+        count_mines(&board, &mut counts);
+    }
+    let expected = NUM_BOARDS as f64 * 64.0 / 81.0;
+    assert!(chi_squared(&counts, expected) < CHI_SQUARED_80, "{counts:?}");
+}
+
+#[test]
+fn deferred_mines_are_uniform_outside_first_click() {
+    let config = Config { safe_first_click: SafeFirstClick::Neighbourhood, ..Config::default() };
+    let mut counts = [0; 81];
+    for seed in 0..NUM_BOARDS {
+        let mut board = GameBoard::new();
+        board.setup_with_seed(&config, seed);
+        board.left_click(4, 4).unwrap();
+        count_mines(&board, &mut counts);
+    }
+
+    let is_protected = |index: usize| (3..=5).contains(&(index / 9)) && (3..=5).contains(&(index % 9));
+    assert!(counts.iter().enumerate().all(|(index, &count)| !is_protected(index) || count == 0));
+    let unprotected: Vec<u64> = counts.iter().enumerate().filter(|(index, _)| !is_protected(*index)).map(|(_, &count)| count).collect();
+    let expected = NUM_BOARDS as f64 * 10.0 / 72.0;
+    assert!(chi_squared(&unprotected, expected) < CHI_SQUARED_71, "{counts:?}");
+}
+
+#[test]
+fn modern_generator_stays_below_max() {
+    let mut rng = ModernRandomNumberGenerator::new();
+    assert!((0..10_000).all(|_| rng.random_u32(3) < 3));
+}
@@ -0,0 +1,206 @@
+//! Checks the BMP and ICO decoder against the shipped resources and against small hand-built bitmaps
+//! covering the formats that the resources do not use.
+
+use minesweeper_rs::resources::{self, BlockSprite, Image, InvalidImage, BLOCK_SIZE};
+
+const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+const BLACK: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+const BLUE: [u8; 4] = [0x00, 0x00, 0xFF, 0xFF];
+const RED: [u8; 4] = [0xFF, 0x00, 0x00, 0xFF];
+const TRANSPARENT: [u8; 4] = [0x00, 0x00, 0x00, 0x00];
+
+fn pixel(image: &Image, x: u32, y: u32) -> [u8; 4] {
+    let index = ((y * image.width + x) * 4) as usize;
+    image.pixels[index..index + 4].try_into().unwrap()
+}
+
+/// Builds a bitmap file with a `BITMAPINFOHEADER`, taking the palette as RGB triples.
+fn bmp(width: i32, height: i32, bit_count: u16, compression: u32, palette: &[[u8; 3]], pixels: &[u8]) -> Vec<u8> {
+    let pixel_offset = 14 + 40 + palette.len() as u32 * 4;
+    let mut data = Vec::new();
+    data.extend_from_slice(b"BM");
+    data.extend_from_slice(&(pixel_offset + pixels.len() as u32).to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&pixel_offset.to_le_bytes());
+    data.extend_from_slice(&40u32.to_le_bytes());
+    data.extend_from_slice(&width.to_le_bytes());
+    data.extend_from_slice(&height.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&bit_count.to_le_bytes());
+    data.extend_from_slice(&compression.to_le_bytes());
+    data.extend_from_slice(&(pixels.len() as u32).to_le_bytes());
+    data.extend_from_slice(&[0; 8]);
+    data.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    for [r, g, b] in palette {
+        data.extend_from_slice(&[*b, *g, *r, 0]);
+    }
+    data.extend_from_slice(pixels);
+    data
+}
+
+#[test]
+fn shipped_bitmaps_have_expected_sizes() {
+    for (data, width, height) in [
+        (resources::BLOCKS_BMP, 16, 256),
+        (resources::BLOCKS_BW_BMP, 16, 256),
+        (resources::BUTTON_BMP, 24, 120),
+        (resources::BUTTON_BW_BMP, 24, 120),
+        (resources::LED_BMP, 13, 276),
+        (resources::LED_BW_BMP, 13, 276),
+    ] {
+        let image = resources::decode_bmp(data).unwrap();
+        assert_eq!((image.width, image.height), (width, height));
+        assert_eq!(image.pixels.len(), (width * height * 4) as usize);
+    }
+}
+
+#[test]
+fn blocks_sprites_decode_in_sheet_order() {
+    let blocks = resources::decode_bmp(resources::BLOCKS_BMP).unwrap();
+    //The covered square has a white highlight along its top and left edges.
+    assert_eq!(pixel(&blocks, 0, 0), WHITE);
+    //The blue stroke of the 1, at the top of its sprite.
+    let one = BlockSprite::Number(1).index() * BLOCK_SIZE;
+    assert_eq!(pixel(&blocks, 8, one + 3), BLUE);
+    //The exploded mine sits on a red background.
+    let exploded = BlockSprite::Exploded.index() * BLOCK_SIZE;
+    assert_eq!(pixel(&blocks, 1, exploded + 1), RED);
+}
+
+#[test]
+fn held_squares_look_pressed_in() {
+    assert_eq!(BlockSprite::Covered.pressed(), BlockSprite::Number(0));
+    assert_eq!(BlockSprite::QuestionMark.pressed(), BlockSprite::QuestionMarkPressed);
+    for sprite in [BlockSprite::Flag, BlockSprite::Number(3), BlockSprite::Number(0)] {
+        assert_eq!(sprite.pressed(), sprite);
+    }
+}
+
+#[test]
+fn black_and_white_bitmaps_use_only_black_and_white() {
+    for data in [resources::BLOCKS_BW_BMP, resources::BUTTON_BW_BMP, resources::LED_BW_BMP] {
+        let image = resources::decode_bmp(data).unwrap();
+        assert!(image.pixels.chunks_exact(4).all(|colour| colour == WHITE || colour == BLACK));
+    }
+}
+
+#[test]
+fn icon_contains_every_size() {
+    let images = resources::decode_ico(resources::WINMINE_ICO).unwrap();
+    let sizes: Vec<(u32, u32)> = images.iter().map(|image| (image.width, image.height)).collect();
+    assert_eq!(sizes, [(32, 32), (32, 32), (48, 48), (32, 32), (16, 16), (48, 48), (32, 32), (16, 16)]);
+    //Every image, whether masked or with an alpha channel, has transparent corners and an opaque centre.
+    for image in &images {
+        assert_eq!(pixel(image, 0, 0)[3], 0);
+        assert_eq!(pixel(image, image.width / 2, image.height / 2)[3], 0xFF);
+    }
+}
+
+#[test]
+fn largest_icon_prefers_alpha_channel() {
+    let icon = resources::decode_ico_largest(resources::WINMINE_ICO).unwrap();
+    assert_eq!((icon.width, icon.height), (48, 48));
+    assert_eq!(icon, resources::decode_ico(resources::WINMINE_ICO).unwrap()[5]);
+}
+
+#[test]
+fn decodes_24_bit() {
+    //Bottom up, so the first row stored is the bottom one. Each row is padded from six bytes to eight.
+    let pixels = [0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0, 0, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0, 0];
+    let image = resources::decode_bmp(&bmp(2, 2, 24, 0, &[], &pixels)).unwrap();
+    assert_eq!([pixel(&image, 0, 0), pixel(&image, 1, 0)], [WHITE, BLACK]);
+    assert_eq!([pixel(&image, 0, 1), pixel(&image, 1, 1)], [RED, BLUE]);
+}
+
+#[test]
+fn decodes_top_down_8_bit() {
+    let palette = [[0, 0, 0], [0xFF, 0xFF, 0xFF], [0xFF, 0, 0]];
+    let pixels = [1, 2, 0, 0, 2, 1, 0, 0];
+    let image = resources::decode_bmp(&bmp(2, -2, 8, 0, &palette, &pixels)).unwrap();
+    assert_eq!([pixel(&image, 0, 0), pixel(&image, 1, 0)], [WHITE, RED]);
+    assert_eq!([pixel(&image, 0, 1), pixel(&image, 1, 1)], [RED, WHITE]);
+}
+
+#[test]
+fn decodes_rle8() {
+    let palette = [[0, 0, 0], [0xFF, 0xFF, 0xFF], [0xFF, 0, 0]];
+    let pixels = [
+        //Bottom row: a run of three whites, then an absolute run of red, black, red padded to a word.
+        3, 1, 0, 3, 2, 0, 2, 0, 0, 0,
+        //Middle row: skip two pixels with a delta, leaving them transparent.
+        0, 2, 2, 0, 4, 2,
+        //End of bitmap, leaving the top row transparent.
+        0, 1,
+    ];
+    let image = resources::decode_bmp(&bmp(6, 3, 8, 1, &palette, &pixels)).unwrap();
+    let bottom: Vec<[u8; 4]> = (0..6).map(|x| pixel(&image, x, 2)).collect();
+    assert_eq!(bottom, [WHITE, WHITE, WHITE, RED, BLACK, RED]);
+    let middle: Vec<[u8; 4]> = (0..6).map(|x| pixel(&image, x, 1)).collect();
+    assert_eq!(middle, [TRANSPARENT, TRANSPARENT, RED, RED, RED, RED]);
+    assert!((0..6).all(|x| pixel(&image, x, 0) == TRANSPARENT));
+}
+
+#[test]
+fn decodes_rle4() {
+    let palette = [[0, 0, 0], [0xFF, 0xFF, 0xFF], [0xFF, 0, 0]];
+    let pixels = [
+        //Five pixels alternating white and red, then an absolute run of black, white, red padded to a word.
+        5, 0x12, 0, 3, 0x01, 0x20,
+        0, 1,
+    ];
+    let image = resources::decode_bmp(&bmp(8, 1, 4, 2, &palette, &pixels)).unwrap();
+    let row: Vec<[u8; 4]> = (0..8).map(|x| pixel(&image, x, 0)).collect();
+    assert_eq!(row, [WHITE, RED, WHITE, RED, WHITE, BLACK, WHITE, RED]);
+}
+
+#[test]
+fn decodes_os2_header() {
+    //A 1-bit, 2x1 bitmap with a BITMAPCOREHEADER and three-byte palette entries.
+    let mut data = Vec::new();
+    data.extend_from_slice(b"BM");
+    data.extend_from_slice(&36u32.to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&32u32.to_le_bytes());
+    data.extend_from_slice(&12u32.to_le_bytes());
+    data.extend_from_slice(&[2, 0, 1, 0, 1, 0, 1, 0]);
+    data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0xFF]);
+    data.extend_from_slice(&[0b0100_0000, 0, 0, 0]);
+    let image = resources::decode_bmp(&data).unwrap();
+    assert_eq!([pixel(&image, 0, 0), pixel(&image, 1, 0)], [BLACK, RED]);
+}
+
+#[test]
+fn rejects_malformed_bitmaps() {
+    assert_eq!(resources::decode_bmp(b"not a bitmap"), Err(InvalidImage));
+    assert_eq!(resources::decode_bmp(&resources::BLOCKS_BMP[..resources::BLOCKS_BMP.len() - 1]), Err(InvalidImage));
+    //16-bit bitmaps are not supported, nor are top down RLE bitmaps.
+    assert_eq!(resources::decode_bmp(&bmp(1, 1, 16, 0, &[], &[0; 4])), Err(InvalidImage));
+    assert_eq!(resources::decode_bmp(&bmp(1, -1, 8, 1, &[[0, 0, 0]], &[0, 1])), Err(InvalidImage));
+    //A palette index past the end of the palette.
+    assert_eq!(resources::decode_bmp(&bmp(1, 1, 8, 0, &[[0, 0, 0]], &[5, 0, 0, 0])), Err(InvalidImage));
+    assert_eq!(resources::decode_ico(&resources::WINMINE_ICO[..100]), Err(InvalidImage));
+}
+
+#[test]
+fn led_digits_show_sign_and_leading_zeros() {
+    use resources::{led_digits, LedSprite::{Digit, Minus}};
+    assert_eq!(led_digits(0), [Digit(0), Digit(0), Digit(0)]);
+    assert_eq!(led_digits(10), [Digit(0), Digit(1), Digit(0)]);
+    assert_eq!(led_digits(999), [Digit(9), Digit(9), Digit(9)]);
+    assert_eq!(led_digits(1234), [Digit(9), Digit(9), Digit(9)]);
+    assert_eq!(led_digits(-5), [Minus, Digit(0), Digit(5)]);
+    assert_eq!(led_digits(-150), [Minus, Digit(9), Digit(9)]);
+}
+
+#[test]
+fn led_sprites_decode_in_sheet_order() {
+    use resources::{LedSprite, LED_HEIGHT};
+    let leds = resources::decode_bmp(resources::LED_BMP).unwrap();
+    let lit = |sprite: LedSprite, x: u32, y: u32| pixel(&leds, x, sprite.index() * LED_HEIGHT + y) == RED;
+    //The middle bar is the only segment lit for a minus sign, and the only one dark for a zero.
+    assert!(lit(LedSprite::Minus, 6, 11) && !lit(LedSprite::Minus, 6, 2));
+    assert!(!lit(LedSprite::Digit(0), 6, 11) && lit(LedSprite::Digit(0), 6, 2));
+    assert!(!lit(LedSprite::Blank, 6, 11) && !lit(LedSprite::Blank, 6, 2));
+    assert!(lit(LedSprite::Digit(8), 6, 11) && lit(LedSprite::Digit(8), 6, 2));
+}
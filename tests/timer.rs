@@ -0,0 +1,66 @@
+//! Checks that `GameTimer` follows the game state and counts seconds the way the original game did.
+
+use std::time::{Duration, Instant};
+
+use minesweeper_rs::{grid::GameState, timer::{GameTimer, MAX_SECONDS}};
+
+#[test]
+fn shows_zero_until_the_game_starts() {
+    let now = Instant::now();
+    let mut timer = GameTimer::new();
+    timer.update_at(GameState::NotStarted, now);
+    assert!(!timer.is_running());
+    assert_eq!(timer.seconds_at(now + Duration::from_secs(5)), 0);
+}
+
+#[test]
+fn counts_the_second_in_progress() {
+    let start = Instant::now();
+    let mut timer = GameTimer::new();
+    timer.update_at(GameState::Playing, start);
+    assert!(timer.is_running());
+    assert_eq!(timer.seconds_at(start), 1);
+    assert_eq!(timer.seconds_at(start + Duration::from_millis(999)), 1);
+    assert_eq!(timer.seconds_at(start + Duration::from_secs(1)), 2);
+    //Later updates while playing do not restart the clock.
+    timer.update_at(GameState::Playing, start + Duration::from_secs(10));
+    assert_eq!(timer.seconds_at(start + Duration::from_secs(10)), 11);
+}
+
+#[test]
+fn stops_when_the_game_ends() {
+    let start = Instant::now();
+    for end_state in [GameState::Won, GameState::Lost { row: 0, column: 0 }] {
+        let mut timer = GameTimer::new();
+        timer.update_at(GameState::Playing, start);
+        timer.update_at(end_state, start + Duration::from_secs(42));
+        assert!(!timer.is_running());
+        assert_eq!(timer.next_tick(), None);
+        assert_eq!(timer.seconds_at(start + Duration::from_secs(100)), 43);
+    }
+}
+
+#[test]
+fn game_ended_by_the_first_reveal_shows_one_second() {
+    let now = Instant::now();
+    let mut timer = GameTimer::new();
+    timer.update_at(GameState::Lost { row: 0, column: 0 }, now);
+    assert_eq!(timer.seconds_at(now + Duration::from_secs(3)), 1);
+}
+
+#[test]
+fn resets_for_a_new_game() {
+    let start = Instant::now();
+    let mut timer = GameTimer::new();
+    timer.update_at(GameState::Won, start);
+    timer.update_at(GameState::NotStarted, start + Duration::from_secs(1));
+    assert_eq!(timer, GameTimer::new());
+}
+
+#[test]
+fn stops_counting_at_the_maximum() {
+    let start = Instant::now();
+    let mut timer = GameTimer::new();
+    timer.update_at(GameState::Playing, start);
+    assert_eq!(timer.seconds_at(start + Duration::from_secs(5000)), MAX_SECONDS);
+}
@@ -0,0 +1,307 @@
+//! Checks the menu bar and dialogs: what the menus show for a config, how clicks open them and choose
+//! items, and the font they are drawn with.
+
+use minesweeper_rs::{config::{BoardLimits, Config, Difficulty, HighScore, SafeFirstClick, Scale}, ui::{font::{self, CHECK_MARK, GLYPH_HEIGHT, GLYPH_WIDTH}, menu::{MenuEntry, MenuHit, MenuKey}, Dialog, DialogAction, MenuBar, MenuCommand, Rect, Shape, Theme}, BoardCode};
+
+/// Wide enough that no menu has to move to fit.
+const WIDTH: u32 = 500;
+
+fn centre(rect: Rect) -> (i32, i32) {
+    (rect.x + rect.width as i32 / 2, rect.y + rect.height as i32 / 2)
+}
+
+/// The entry of `menu` labelled `label`, and its index.
+fn entry<'a>(menu_bar: &'a MenuBar, menu: usize, label: &str) -> (usize, &'a MenuEntry) {
+    menu_bar.menus()[menu].entries.iter().enumerate().find(|(_, entry)| matches!(entry, MenuEntry::Item { label: item_label, .. } if item_label == label)).unwrap()
+}
+
+#[test]
+fn font_covers_ascii_and_the_check_mark() {
+    let image = font::font_image();
+    assert_eq!(image.width, GLYPH_WIDTH);
+    assert_eq!(image.height, 96 * GLYPH_HEIGHT);
+    assert_eq!(image.pixels.len(), (image.width * image.height * 4) as usize);
+    assert_eq!(font::glyph_index(' '), 0);
+    assert_eq!(font::glyph_index('~'), 94);
+    assert_eq!(font::glyph_index(CHECK_MARK), 95);
+    //Characters the font lacks are drawn as question marks.
+    assert_eq!(font::glyph_index('é'), font::glyph_index('?'));
+}
+
+#[test]
+fn text_width_leaves_out_the_last_gap() {
+    assert_eq!(font::text_width(""), 0);
+    assert_eq!(font::text_width("A"), GLYPH_WIDTH);
+    assert_eq!(font::text_width("New"), 17);
+}
+
+#[test]
+fn menus_check_the_current_settings() {
+    let config = Config { difficulty: Difficulty::INTERMEDIATE, marks_enabled: false, scale: Scale::Fixed(2), ..Config::default() };
+    let menu_bar = MenuBar::new(&config);
+    for (menu, label, expected) in [(0, "Beginner", false), (0, "Intermediate", true), (0, "Marks (?)", false), (1, "Auto Zoom", false), (1, "2x", true)] {
+        let (_, MenuEntry::Item { checked, .. }) = entry(&menu_bar, menu, label) else { unreachable!() };
+        assert_eq!(*checked, expected, "{label}");
+    }
+}
+
+#[test]
+fn custom_is_checked_for_a_custom_board() {
+    let config = Config { difficulty: Difficulty::new(20, 10, 30), ..Config::default() };
+    let mut menu_bar = MenuBar::new(&config);
+    let (index, MenuEntry::Item { checked: true, .. }) = entry(&menu_bar, 0, "Custom...") else { panic!("Custom... is not checked") };
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    assert_eq!(menu_bar.release(WIDTH, Some(centre(menu_bar.entry_rect(WIDTH, 0, index)))), Some(MenuCommand::CustomField));
+}
+
+#[test]
+fn shortcuts_follow_the_key_bindings() {
+    let menu_bar = MenuBar::new(&Config::default());
+    for (label, expected) in [("New", Some("F2")), ("Beginner", Some("1")), ("Best Times...", None)] {
+        let (_, MenuEntry::Item { shortcut, .. }) = entry(&menu_bar, 0, label) else { unreachable!() };
+        assert_eq!(shortcut.as_deref(), expected, "{label}");
+    }
+}
+
+#[test]
+fn clicking_a_title_opens_and_closes_its_menu() {
+    let mut menu_bar = MenuBar::new(&Config::default());
+    assert!(!menu_bar.press(WIDTH, Some((5, 100))));
+    assert!(menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(1)))));
+    assert_eq!(menu_bar.open_menu(), Some(1));
+    assert!(menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(1)))));
+    assert!(!menu_bar.is_open());
+    //A click elsewhere closes the menu without reaching the game.
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    assert!(menu_bar.press(WIDTH, Some((500, 500))));
+    assert!(!menu_bar.is_open());
+}
+
+#[test]
+fn releasing_over_an_item_chooses_it() {
+    let mut menu_bar = MenuBar::new(&Config::default());
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    let (index, _) = entry(&menu_bar, 0, "Expert");
+    let position = centre(menu_bar.entry_rect(WIDTH, 0, index));
+    assert_eq!(menu_bar.hit(WIDTH, position), Some(MenuHit::Entry(index)));
+    assert_eq!(menu_bar.release(WIDTH, Some(position)), Some(MenuCommand::Difficulty(Difficulty::EXPERT)));
+    assert!(!menu_bar.is_open());
+}
+
+#[test]
+fn hovering_over_another_title_switches_menus() {
+    let mut menu_bar = MenuBar::new(&Config::default());
+    assert!(!menu_bar.hover(WIDTH, Some(centre(menu_bar.title_rect(2)))));
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    assert!(menu_bar.hover(WIDTH, Some(centre(menu_bar.title_rect(2)))));
+    assert_eq!(menu_bar.open_menu(), Some(2));
+    assert!(!menu_bar.hover(WIDTH, Some(centre(menu_bar.title_rect(2)))));
+}
+
+#[test]
+fn keys_move_around_the_open_menu() {
+    let mut menu_bar = MenuBar::new(&Config::default());
+    assert_eq!(menu_bar.key(MenuKey::Down), None);
+    assert!(!menu_bar.is_open());
+
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    //Nothing is highlighted yet, so there is nothing to choose.
+    assert_eq!(menu_bar.key(MenuKey::Choose), None);
+    assert!(menu_bar.is_open());
+    //Down past the separator to Beginner, and up from the top wraps around to Exit.
+    for _ in 0..3 {
+        menu_bar.key(MenuKey::Down);
+    }
+    assert_eq!(menu_bar.key(MenuKey::Choose), Some(MenuCommand::Difficulty(Difficulty::BEGINNER)));
+    assert!(!menu_bar.is_open());
+
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    menu_bar.key(MenuKey::Up);
+    assert_eq!(menu_bar.key(MenuKey::Choose), Some(MenuCommand::Exit));
+
+    //Left from the first menu wraps around to Help.
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    menu_bar.key(MenuKey::Left);
+    assert_eq!(menu_bar.open_menu(), Some(2));
+    menu_bar.key(MenuKey::Right);
+    assert_eq!(menu_bar.open_menu(), Some(0));
+    menu_bar.key(MenuKey::Close);
+    assert!(!menu_bar.is_open());
+}
+
+#[test]
+fn menus_stay_inside_a_narrow_window() {
+    //A beginner board at 1x.
+    let width = 168;
+    let mut menu_bar = MenuBar::new(&Config::default());
+    for menu in 0..menu_bar.menus().len() {
+        let dropdown = menu_bar.dropdown_rect(width, menu);
+        assert!(dropdown.x >= 0 && dropdown.x + dropdown.width as i32 <= width as i32, "{} spans {}..{}", menu_bar.menus()[menu].title, dropdown.x, dropdown.x + dropdown.width as i32);
+    }
+    //The Help menu moves left to fit, and its items can still be chosen where they are drawn.
+    assert!(menu_bar.dropdown_rect(width, 2).x < menu_bar.title_rect(2).x);
+    menu_bar.press(width, Some(centre(menu_bar.title_rect(2))));
+    assert_eq!(menu_bar.release(width, Some(centre(menu_bar.entry_rect(width, 2, 0)))), Some(MenuCommand::About));
+    //With room to spare, a menu hangs straight below its title.
+    assert_eq!(menu_bar.dropdown_rect(WIDTH, 2).x, menu_bar.title_rect(2).x);
+}
+
+#[test]
+fn best_times_lists_each_difficulty() {
+    let config = Config { intermediate_score: HighScore { name: String::from("Ada"), time: 42 }, ..Config::default() };
+    let dialog = Dialog::best_times(&config);
+    assert!(dialog.lines.contains(&String::from("Intermediate: 42 seconds")));
+    assert!(dialog.lines.contains(&String::from("  Ada")));
+    assert_eq!(dialog.default_action(), DialogAction::Close);
+}
+
+#[test]
+fn dialog_buttons_act_when_released_over_them() {
+    let window = (200, 200);
+    let mut dialog = Dialog::best_times(&Config::default());
+    let reset = centre(dialog.button_rect(window, 0));
+    let ok = centre(dialog.button_rect(window, 1));
+    //Dragging off a button before letting go does nothing.
+    dialog.press(window, Some(reset));
+    assert_eq!(dialog.release(window, Some(ok)), None);
+    dialog.press(window, Some(reset));
+    assert_eq!(dialog.release(window, Some(reset)), Some(DialogAction::ResetScores));
+}
+
+#[test]
+fn custom_field_starts_from_the_current_board() {
+    let dialog = Dialog::custom_field(&Config { difficulty: Difficulty::EXPERT, ..Config::default() });
+    let values: Vec<_> = dialog.fields.iter().map(|field| field.value.as_str()).collect();
+    assert_eq!(values, ["16", "30", "99"]);
+    assert_eq!(dialog.default_action(), DialogAction::CustomField);
+}
+
+#[test]
+fn custom_field_takes_digits_backspace_and_tab() {
+    let mut dialog = Dialog::custom_field(&Config::default());
+    for character in "\u{8}2x0\t\u{8}\u{8}".chars() {
+        dialog.type_character(character);
+    }
+    assert_eq!(dialog.fields[0].value, "20");
+    assert_eq!(dialog.fields[1].value, "");
+    assert_eq!(dialog.focused, 1);
+    //Fields stop taking digits once they hold as many as the largest value allowed.
+    for _ in 0..10 {
+        dialog.type_character('1');
+    }
+    assert_eq!(dialog.fields[1].value, "11");
+}
+
+#[test]
+fn custom_field_lengths_follow_the_board_limits() {
+    let dialog = Dialog::custom_field(&Config::default());
+    let max_lengths: Vec<_> = dialog.fields.iter().map(|field| field.max_length).collect();
+    assert_eq!(max_lengths, [2, 2, 3]);
+
+    //A 1000x1000 board can hold 998001 mines, all of which must fit.
+    let limits = BoardLimits { max_width: 1000, max_height: 1000, ..BoardLimits::CLASSIC };
+    let window = (200, 200);
+    let mut dialog = Dialog::custom_field(&Config { board_limits: limits, ..Config::default() });
+    for field in 0..3 {
+        dialog.fields[field].value.clear();
+        dialog.focused = field;
+        for character in "9980019".chars() {
+            dialog.type_character(character);
+        }
+    }
+    let values: Vec<_> = dialog.fields.iter().map(|field| field.value.as_str()).collect();
+    assert_eq!(values, ["9980", "9980", "998001"]);
+    //The fields widen to fit six digits.
+    assert!(dialog.field_rect(window, 2).width > Dialog::custom_field(&Config::default()).field_rect(window, 2).width);
+    assert_eq!(dialog.custom_difficulty(&limits), None);
+    dialog.fields[0].value = String::from("1000");
+    dialog.fields[1].value = String::from("1000");
+    assert_eq!(dialog.custom_difficulty(&limits), Difficulty::try_with_limits(1000, 1000, 998001, &limits).ok());
+}
+
+#[test]
+fn custom_field_explains_values_outside_the_limits() {
+    let mut dialog = Dialog::custom_field(&Config::default());
+    dialog.fields[1].value = String::from("31");
+    dialog.focused = 0;
+    assert_eq!(dialog.custom_difficulty(&BoardLimits::CLASSIC), None);
+    assert_eq!(dialog.error.as_deref(), Some("width must be 9 to 30"));
+    assert_eq!(dialog.focused, 1);
+
+    dialog.fields[1].value = String::from("30");
+    dialog.fields[2].value.clear();
+    assert_eq!(dialog.custom_difficulty(&BoardLimits::CLASSIC), None);
+    assert_eq!(dialog.error.as_deref(), Some("mines must be 10 to 232"));
+
+    dialog.fields[2].value = String::from("50");
+    assert_eq!(dialog.custom_difficulty(&BoardLimits::CLASSIC), Some(Difficulty::new(30, 9, 50)));
+}
+
+#[test]
+fn clicking_a_field_focuses_it() {
+    let window = (200, 200);
+    let mut dialog = Dialog::custom_field(&Config::default());
+    dialog.press(window, Some(centre(dialog.field_rect(window, 2))));
+    assert_eq!(dialog.focused, 2);
+    assert_eq!(dialog.release(window, Some(centre(dialog.field_rect(window, 2)))), None);
+}
+
+#[test]
+fn board_code_item_opens_the_dialog() {
+    let mut menu_bar = MenuBar::new(&Config::default());
+    let (index, _) = entry(&menu_bar, 0, "Board Code...");
+    menu_bar.press(WIDTH, Some(centre(menu_bar.title_rect(0))));
+    assert_eq!(menu_bar.release(WIDTH, Some(centre(menu_bar.entry_rect(WIDTH, 0, index)))), Some(MenuCommand::BoardCode));
+}
+
+#[test]
+fn board_code_dialog_shows_the_code_to_copy() {
+    let board_code = BoardCode::new(Difficulty::new(30, 24, 667), SafeFirstClick::Neighbourhood, u64::MAX);
+    let dialog = Dialog::board_code(Some(board_code), &BoardLimits::CLASSIC);
+    assert!(dialog.lines.contains(&board_code.to_string()));
+    let actions: Vec<_> = dialog.buttons.iter().map(|button| button.action).collect();
+    assert_eq!(actions, [DialogAction::PlayBoardCode, DialogAction::CopyBoardCode, DialogAction::Close]);
+    assert_eq!(dialog.default_action(), DialogAction::PlayBoardCode);
+    //Even the longest classic code fits over a beginner board.
+    let rect = dialog.rect((168, 225));
+    assert!(rect.x >= 0 && rect.x + rect.width as i32 <= 168, "{rect:?}");
+
+    //Boards from the legacy generator have no code, so there is nothing to copy.
+    let dialog = Dialog::board_code(None, &BoardLimits::CLASSIC);
+    assert!(dialog.buttons.iter().all(|button| button.action != DialogAction::CopyBoardCode));
+}
+
+#[test]
+fn board_code_dialog_reads_the_typed_code() {
+    let mut dialog = Dialog::board_code(None, &BoardLimits::CLASSIC);
+    for character in "i-1Z4 *kq\u{8}Q0M9XH3TA-n".chars() {
+        dialog.type_character(character);
+    }
+    assert_eq!(dialog.fields[0].value, "i-1Z4kQ0M9XH3TA-n");
+    assert_eq!(dialog.entered_board_code(&BoardLimits::CLASSIC), "i-1Z4KQ0M9XH3TA-n".parse().ok());
+
+    dialog.fields[0].value = String::from("31x9x10-1");
+    assert_eq!(dialog.entered_board_code(&BoardLimits::CLASSIC), None);
+    assert_eq!(dialog.error.as_deref(), Some("invalid board code"));
+    //Raised limits let the same code through.
+    let limits = BoardLimits { max_width: 31, ..BoardLimits::CLASSIC };
+    assert!(dialog.entered_board_code(&limits).is_some());
+}
+
+#[test]
+fn board_code_field_takes_the_longest_code_and_shows_its_end() {
+    let mut dialog = Dialog::board_code(None, &BoardLimits::CLASSIC);
+    let longest = BoardCode::new(Difficulty::new(30, 24, 667), SafeFirstClick::Square, 0).to_string();
+    for character in longest.chars().chain("000".chars()) {
+        dialog.type_character(character);
+    }
+    assert_eq!(dialog.fields[0].value, longest);
+    let shapes = dialog.shapes((168, 225), Theme::COLOUR);
+    let field = dialog.field_rect((168, 225), 0);
+    let text = shapes.iter().find_map(|shape| match shape {
+        Shape::Text { position, text, .. } if field.contains(*position) => Some(text.clone()),
+        _ => None,
+    }).unwrap();
+    assert!(longest.ends_with(&text) && text.len() < longest.len(), "{text}");
+    assert!(font::text_width(&text) < field.width);
+}